@@ -7,10 +7,12 @@ use std::time::Duration;
 pub use self::thread::Thread;
 pub use self::process::Process;
 pub use self::generic::{Generic, GenericBuilder};
+pub use self::simulated::Simulated;
 
 pub mod thread;
 pub mod process;
 pub mod generic;
+pub mod simulated;
 
 pub mod canary;
 pub mod counters;
@@ -0,0 +1,196 @@
+//! Simulated communication among workers on a single thread.
+//!
+//! The simulated allocators of a group of workers share a [Network], which holds each sent
+//! message in flight until it is explicitly delivered. Messages between the same pair of
+//! workers on the same channel are delivered in the order they were sent, but a driver may
+//! otherwise deliver messages in any order, for example to explore interleavings.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::Rc;
+
+use crate::allocator::{Allocate, Thread};
+use crate::{Push, Pull, Message};
+
+/// Messages in flight among simulated workers.
+pub struct Network {
+    peers: usize,
+    // below: `Rc<dyn Any>` is a `Rc<RefCell<Channel<T>>>`, and is retained until all workers allocate.
+    typed: HashMap</* channel id */ usize, (Rc<dyn Any>, usize)>,
+    channels: BTreeMap</* channel id */ usize, Rc<RefCell<dyn Deliver>>>,
+    events: Vec<Rc<RefCell<Vec<usize>>>>,
+}
+
+impl Network {
+    /// The number of simulated workers.
+    pub fn peers(&self) -> usize { self.peers }
+
+    /// Lists the non-empty queues of messages in flight, as `(channel, source, target)`.
+    ///
+    /// The queues are listed in a deterministic order.
+    pub fn in_flight(&self) -> Vec<(usize, usize, usize)> {
+        let mut result = Vec::new();
+        for (channel, queues) in self.channels.iter() {
+            for (source, target) in queues.borrow().non_empty() {
+                result.push((*channel, source, target));
+            }
+        }
+        result
+    }
+
+    /// Delivers the oldest message in flight from `source` to `target` on `channel`.
+    ///
+    /// Returns `false` if there was no such message.
+    pub fn deliver(&mut self, channel: usize, source: usize, target: usize) -> bool {
+        let delivered =
+        self.channels
+            .get(&channel)
+            .map(|queues| queues.borrow_mut().deliver(source, target))
+            .unwrap_or(false);
+        if delivered {
+            self.events[target].borrow_mut().push(channel);
+        }
+        delivered
+    }
+}
+
+/// Moves messages of one channel from in flight to delivered.
+trait Deliver {
+    /// Pairs `(source, target)` with messages in flight.
+    fn non_empty(&self) -> Vec<(usize, usize)>;
+    /// Delivers the oldest message in flight from `source` to `target`.
+    fn deliver(&mut self, source: usize, target: usize) -> bool;
+}
+
+/// The message queues of one channel.
+struct Channel<T> {
+    peers: usize,
+    /// Messages in flight, indexed by `source * peers + target`.
+    in_flight: Vec<VecDeque<Message<T>>>,
+    /// Delivered messages, indexed by target.
+    delivered: Vec<VecDeque<Message<T>>>,
+}
+
+impl<T> Deliver for Channel<T> {
+    fn non_empty(&self) -> Vec<(usize, usize)> {
+        self.in_flight
+            .iter()
+            .enumerate()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(index, _)| (index / self.peers, index % self.peers))
+            .collect()
+    }
+    fn deliver(&mut self, source: usize, target: usize) -> bool {
+        if let Some(message) = self.in_flight[source * self.peers + target].pop_front() {
+            self.delivered[target].push_back(message);
+            true
+        }
+        else {
+            false
+        }
+    }
+}
+
+/// An allocator for one of several workers simulated on the same thread.
+pub struct Simulated {
+    inner: Thread,
+    index: usize,
+    peers: usize,
+    network: Rc<RefCell<Network>>,
+}
+
+impl Simulated {
+    /// Allocates a list of connected simulated allocators, and their shared network.
+    pub fn new_vector(peers: usize) -> (Vec<Simulated>, Rc<RefCell<Network>>) {
+        let inners = (0 .. peers).map(|_| Thread::new()).collect::<Vec<_>>();
+        let network = Rc::new(RefCell::new(Network {
+            peers,
+            typed: HashMap::new(),
+            channels: BTreeMap::new(),
+            events: inners.iter().map(|inner| inner.events().clone()).collect(),
+        }));
+        let allocators =
+        inners
+            .into_iter()
+            .enumerate()
+            .map(|(index, inner)| Simulated { inner, index, peers, network: network.clone() })
+            .collect();
+        (allocators, network)
+    }
+}
+
+impl Allocate for Simulated {
+    fn index(&self) -> usize { self.index }
+    fn peers(&self) -> usize { self.peers }
+    fn allocate<T: 'static>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+
+        let mut network = self.network.borrow_mut();
+        let peers = self.peers;
+
+        // The first worker to allocate the channel creates its queues.
+        let channel = {
+            let entry = network.typed.entry(identifier).or_insert_with(|| {
+                let channel = Rc::new(RefCell::new(Channel::<T> {
+                    peers,
+                    in_flight: (0 .. peers * peers).map(|_| VecDeque::new()).collect(),
+                    delivered: (0 .. peers).map(|_| VecDeque::new()).collect(),
+                }));
+                (channel as Rc<dyn Any>, 0)
+            });
+            entry.1 += 1;
+            entry.0
+                .clone()
+                .downcast::<RefCell<Channel<T>>>()
+                .unwrap_or_else(|_| panic!("failed to correctly cast channel"))
+        };
+
+        // Retain the typed channel only until all workers have allocated it.
+        if network.typed[&identifier].1 == peers {
+            network.typed.remove(&identifier);
+        }
+        network.channels.entry(identifier).or_insert_with(|| channel.clone() as Rc<RefCell<dyn Deliver>>);
+
+        let pushers =
+        (0 .. peers)
+            .map(|target| Box::new(Pusher { source: self.index, target, channel: channel.clone() }) as Box<dyn Push<Message<T>>>)
+            .collect();
+        let puller = Box::new(Puller { index: self.index, channel, current: None });
+
+        (pushers, puller)
+    }
+    fn events(&self) -> &Rc<RefCell<Vec<usize>>> {
+        self.inner.events()
+    }
+}
+
+/// The push half of a simulated channel, from one worker to another.
+struct Pusher<T> {
+    source: usize,
+    target: usize,
+    channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Push<Message<T>> for Pusher<T> {
+    fn push(&mut self, element: &mut Option<Message<T>>) {
+        if let Some(element) = element.take() {
+            let mut channel = self.channel.borrow_mut();
+            let peers = channel.peers;
+            channel.in_flight[self.source * peers + self.target].push_back(element);
+        }
+    }
+}
+
+/// The pull half of a simulated channel, for one worker.
+struct Puller<T> {
+    index: usize,
+    channel: Rc<RefCell<Channel<T>>>,
+    current: Option<Message<T>>,
+}
+
+impl<T> Pull<Message<T>> for Puller<T> {
+    fn pull(&mut self) -> &mut Option<Message<T>> {
+        self.current = self.channel.borrow_mut().delivered[self.index].pop_front();
+        &mut self.current
+    }
+}
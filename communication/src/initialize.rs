@@ -5,12 +5,14 @@ use std::thread;
 use std::io::BufRead;
 #[cfg(feature = "getopts")]
 use getopts;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::panic::{self, AssertUnwindSafe};
 
 use std::any::Any;
 
 use crate::allocator::thread::ThreadBuilder;
-use crate::allocator::{AllocateBuilder, Process, Generic, GenericBuilder};
+use crate::allocator::{Allocate, AllocateBuilder, Process, Generic, GenericBuilder};
 use crate::allocator::zero_copy::allocator_process::ProcessBuilder;
 use crate::allocator::zero_copy::initialize::initialize_networking;
 
@@ -304,27 +306,138 @@ where
     A: AllocateBuilder+'static,
     T: Send+'static,
     F: Fn(<A as AllocateBuilder>::Allocator)->T+Send+Sync+'static
+{
+    initialize_cancellable(builders, others, move |allocator, _cancel| func(allocator))
+}
+
+/// Initializes computation and runs a distributed computation, with cancellation.
+///
+/// This version of `initialize_from` additionally provides each worker with a
+/// `CancelHandle`, shared with the resulting worker guard. Workers should regularly
+/// consult `CancelHandle::is_cancelled`, and wind down their work once it is set.
+/// The handle unparks all worker threads when it is cancelled.
+///
+/// If a worker panics, the computation is poisoned: the panic is recorded in the
+/// handle, which is then cancelled so that the other workers stop promptly rather
+/// than wait on the failed worker. The panic is reported by `CancelHandle::poisoned`.
+pub fn initialize_cancellable<A, T, F>(
+    builders: Vec<A>,
+    others: Box<dyn Any+Send>,
+    func: F,
+) -> Result<WorkerGuards<T>,String>
+where
+    A: AllocateBuilder+'static,
+    T: Send+'static,
+    F: Fn(<A as AllocateBuilder>::Allocator, CancelHandle)->T+Send+Sync+'static
 {
     let logic = Arc::new(func);
+    let cancel = CancelHandle::new();
     let mut guards = Vec::new();
     for (index, builder) in builders.into_iter().enumerate() {
         let clone = logic.clone();
-        guards.push(thread::Builder::new()
+        let cancel_clone = cancel.clone();
+        let guard = thread::Builder::new()
                             .name(format!("timely:work-{}", index))
                             .spawn(move || {
                                 let communicator = builder.build();
-                                (*clone)(communicator)
+                                let worker_index = communicator.index();
+                                let poison = cancel_clone.clone();
+                                let result = panic::catch_unwind(AssertUnwindSafe(|| (*clone)(communicator, cancel_clone)));
+                                result.unwrap_or_else(|payload| {
+                                    poison.poison(worker_index, payload_string(&*payload));
+                                    panic::resume_unwind(payload)
+                                })
                             })
-                            .map_err(|e| format!("{:?}", e))?);
+                            .map_err(|e| format!("{:?}", e))?;
+        cancel.state.threads.lock().expect("mutex error?").push(guard.thread().clone());
+        guards.push(guard);
     }
 
-    Ok(WorkerGuards { guards, others })
+    Ok(WorkerGuards { guards, others, cancel })
+}
+
+/// A shared request that the workers of a computation stop.
+///
+/// Cancellation is cooperative: setting the request unparks the worker threads,
+/// and it is up to the workers to observe the request and wind down.
+#[derive(Clone, Debug)]
+pub struct CancelHandle {
+    state: Arc<CancelState>,
+}
+
+#[derive(Debug)]
+struct CancelState {
+    cancelled: AtomicBool,
+    threads: Mutex<Vec<thread::Thread>>,
+    /// The index and panic message of the first worker to panic.
+    poison: Mutex<Option<(usize, String)>>,
+}
+
+/// Renders a panic payload as a string, if it is one.
+fn payload_string(payload: &(dyn Any+Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    }
+    else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    }
+    else {
+        "non-string panic payload".to_string()
+    }
+}
+
+impl CancelHandle {
+    /// Creates a new handle, which is not yet cancelled.
+    pub fn new() -> Self {
+        CancelHandle {
+            state: Arc::new(CancelState {
+                cancelled: AtomicBool::new(false),
+                threads: Mutex::new(Vec::new()),
+                poison: Mutex::new(None),
+            })
+        }
+    }
+
+    /// Requests that the workers stop, and unparks their threads.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        for thread in self.state.threads.lock().expect("mutex error?").iter() {
+            thread.unpark();
+        }
+    }
+
+    /// True if cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Records that worker `index` panicked with `message`, and cancels the computation.
+    ///
+    /// Only the first panic is retained.
+    pub fn poison(&self, index: usize, message: String) {
+        if let Ok(mut poison) = self.state.poison.lock() {
+            poison.get_or_insert((index, message));
+        }
+        self.cancel();
+    }
+
+    /// The index and panic message of the first worker to panic, if any.
+    pub fn poisoned(&self) -> Option<(usize, String)> {
+        self.state.poison.lock().ok().and_then(|poison| poison.clone())
+    }
+}
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Maintains `JoinHandle`s for worker threads.
 pub struct WorkerGuards<T:Send+'static> {
     guards: Vec<::std::thread::JoinHandle<T>>,
     others: Box<dyn Any+Send>,
+    cancel: CancelHandle,
 }
 
 impl<T:Send+'static> WorkerGuards<T> {
@@ -339,11 +452,24 @@ impl<T:Send+'static> WorkerGuards<T> {
         &self.others
     }
 
+    /// Returns a handle that can be used to ask the workers to stop.
+    ///
+    /// The handle can be moved to other threads, for example a signal handler,
+    /// and remains usable after the guards have been joined or dropped.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
     /// Waits on the worker threads and returns the results they produce.
+    ///
+    /// Workers that panicked report their panic message as an error. The remaining workers
+    /// report the results they produced, even if they stopped early because another worker
+    /// panicked. The first worker to panic, which caused the others to stop, is reported by
+    /// [CancelHandle::poisoned] on a handle obtained from [WorkerGuards::cancel_handle].
     pub fn join(mut self) -> Vec<Result<T, String>> {
         self.guards
             .drain(..)
-            .map(|guard| guard.join().map_err(|e| format!("{:?}", e)))
+            .map(|guard| guard.join().map_err(|e| format!("Worker panic: {}", payload_string(&*e))))
             .collect()
     }
 }
@@ -351,7 +477,12 @@ impl<T:Send+'static> WorkerGuards<T> {
 impl<T:Send+'static> Drop for WorkerGuards<T> {
     fn drop(&mut self) {
         for guard in self.guards.drain(..) {
-            guard.join().expect("Worker panic");
+            if guard.join().is_err() {
+                match self.cancel.poisoned() {
+                    Some((index, message)) => panic!("Worker panic: worker {} panicked first: {}", index, message),
+                    None => panic!("Worker panic"),
+                }
+            }
         }
         // println!("WORKER THREADS JOINED");
     }
@@ -104,7 +104,7 @@ use abomonation::Abomonation;
 
 pub use allocator::Generic as Allocator;
 pub use allocator::Allocate;
-pub use initialize::{initialize, initialize_from, Config, WorkerGuards};
+pub use initialize::{initialize, initialize_from, initialize_cancellable, CancelHandle, Config, WorkerGuards};
 pub use message::Message;
 
 /// A composite trait for types that may be used with channels.
@@ -349,6 +349,10 @@ mod container {
             self.local.is_empty()
         }
 
+        fn size_in_bytes(&self) -> Option<usize> {
+            Some(self.summed_heap_size().0)
+        }
+
         fn clear(&mut self) {
             TimelyStack::clear(self)
         }
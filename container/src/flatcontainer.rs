@@ -11,6 +11,12 @@ impl<R: Region + Clone + 'static> Container for FlatStack<R> {
         self.len()
     }
 
+    fn size_in_bytes(&self) -> Option<usize> {
+        let mut size = 0;
+        self.heap_size(|length, _capacity| size += length);
+        Some(size)
+    }
+
     fn clear(&mut self) {
         self.clear()
     }
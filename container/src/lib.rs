@@ -39,6 +39,14 @@ pub trait Container: Default + Clone + 'static {
         self.len() == 0
     }
 
+    /// An estimate of the number of bytes occupied by the elements of this container, if known.
+    ///
+    /// The estimate describes the contents rather than the allocation, and is used to report
+    /// the volume of data sent on channels. The default implementation returns `None`.
+    fn size_in_bytes(&self) -> Option<usize> {
+        None
+    }
+
     /// Remove all contents from `self` while retaining allocated memory.
     /// After calling `clear`, `is_empty` must return `true` and `len` 0.
     fn clear(&mut self);
@@ -197,6 +205,11 @@ impl<T: Clone + 'static> Container for Vec<T> {
         Vec::is_empty(self)
     }
 
+    /// The in-memory size of the elements, not including memory they own indirectly.
+    fn size_in_bytes(&self) -> Option<usize> {
+        Some(Vec::len(self) * std::mem::size_of::<T>())
+    }
+
     fn clear(&mut self) { Vec::clear(self) }
 
     type Iter<'a> = std::slice::Iter<'a, T>;
@@ -265,6 +278,10 @@ mod rc {
             std::ops::Deref::deref(self).is_empty()
         }
 
+        fn size_in_bytes(&self) -> Option<usize> {
+            std::ops::Deref::deref(self).size_in_bytes()
+        }
+
         fn clear(&mut self) {
             // Try to reuse the allocation if possible
             if let Some(inner) = Rc::get_mut(self) {
@@ -306,6 +323,10 @@ mod arc {
             std::ops::Deref::deref(self).is_empty()
         }
 
+        fn size_in_bytes(&self) -> Option<usize> {
+            std::ops::Deref::deref(self).size_in_bytes()
+        }
+
         fn clear(&mut self) {
             // Try to reuse the allocation if possible
             if let Some(inner) = Arc::get_mut(self) {
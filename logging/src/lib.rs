@@ -36,6 +36,37 @@ impl<Id: Clone+'static> Registry<Id> {
         self.insert_logger(name, logger)
     }
 
+    /// Binds a log name to an action on log event batches, which only receives events accepted by `filter`.
+    ///
+    /// The filter is applied as events are logged, before they are timestamped and buffered, and
+    /// so rejected events cost little more than their construction. Otherwise this method behaves
+    /// as `insert`.
+    pub fn insert_filtered<T: 'static, P: FnMut(&T)->bool+'static, F: FnMut(&Duration, &mut Vec<(Duration, Id, T)>)+'static>(
+        &mut self,
+        name: &str,
+        filter: P,
+        action: F) -> Option<Box<dyn Any>>
+    {
+        let logger = Logger::<T, Id>::new_filtered(self.time, Duration::default(), self.id.clone(), filter, action);
+        self.insert_logger(name, logger)
+    }
+
+    /// Binds a log name to an action on log event batches, which only receives a fraction `rate` of events.
+    ///
+    /// Events are selected as by [Sample], before they are timestamped and buffered. Each event is
+    /// selected independently, so that of related events, such as the start and stop of an operator
+    /// scheduling, some may be selected without the others. To sample only some events, or related
+    /// events together, use a [Sample] in the filter of `insert_filtered`.
+    pub fn insert_sampled<T: 'static, F: FnMut(&Duration, &mut Vec<(Duration, Id, T)>)+'static>(
+        &mut self,
+        name: &str,
+        rate: f64,
+        action: F) -> Option<Box<dyn Any>>
+    {
+        let mut sample = Sample::new(rate);
+        self.insert_filtered(name, move |_: &T| sample.select(), action)
+    }
+
     /// Binds a log name to a logger.
     pub fn insert_logger<T: 'static>(
         &mut self,
@@ -86,6 +117,50 @@ impl<Id> Flush for Registry<Id> {
     }
 }
 
+/// Selects a fraction of a sequence of events.
+///
+/// Selection is deterministic and evenly spaced: with a rate of `0.01`, every hundredth event is
+/// selected. This avoids both a source of randomness and the bursts it would produce.
+///
+/// Each selection is independent of the events themselves. To select related events together,
+/// select only the first of them, and remember the selection for the rest.
+///
+/// # Examples
+/// ```
+/// use timely_logging::Sample;
+///
+/// let mut sample = Sample::new(0.25);
+/// let selected = (0 .. 100).filter(|_| sample.select()).count();
+/// assert_eq!(selected, 25);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sample {
+    rate: f64,
+    /// Accumulated rate, selecting an event each time it reaches one.
+    credit: f64,
+}
+
+impl Sample {
+    /// Creates a sample of a fraction `rate` of events, which is clamped to between zero and one.
+    pub fn new(rate: f64) -> Self {
+        let rate = if rate.is_nan() { 0.0 } else { rate.max(0.0).min(1.0) };
+        Sample { rate, credit: 0.0 }
+    }
+
+    /// Indicates whether the next event is selected.
+    #[inline]
+    pub fn select(&mut self) -> bool {
+        self.credit += self.rate;
+        if self.credit >= 1.0 {
+            self.credit -= 1.0;
+            true
+        }
+        else {
+            false
+        }
+    }
+}
+
 /// A buffering logger.
 #[derive(Debug)]
 pub struct Logger<T, E> {
@@ -108,6 +183,8 @@ struct LoggerInner<T, E, A: ?Sized + FnMut(&Duration, &mut Vec<(Duration, E, T)>
     offset: Duration,
     /// shared buffer of accumulated log events
     buffer: Vec<(Duration, E, T)>,
+    /// predicate selecting the events to buffer, if not all.
+    filter: Option<Box<dyn FnMut(&T)->bool>>,
     /// action to take on full log buffers.
     action: A,
 }
@@ -124,11 +201,24 @@ impl<T, E: Clone> Logger<T, E> {
             offset,
             action,
             buffer: Vec::with_capacity(LoggerInner::<T, E, F>::buffer_capacity()),
+            filter: None,
         };
         let inner = Rc::new(RefCell::new(inner));
         Logger { inner }
     }
 
+    /// Allocates a new shareable logger bound to a write destination, which only records events accepted by `filter`.
+    pub fn new_filtered<P, F>(time: Instant, offset: Duration, id: E, filter: P, action: F) -> Self
+    where
+        T: 'static,
+        P: FnMut(&T)->bool+'static,
+        F: FnMut(&Duration, &mut Vec<(Duration, E, T)>)+'static
+    {
+        let logger = Self::new(time, offset, id, action);
+        logger.inner.borrow_mut().filter = Some(Box::new(filter));
+        logger
+    }
+
     /// Logs an event.
     ///
     /// The event has its timestamp recorded at the moment of logging, but it may be delayed
@@ -190,9 +280,15 @@ impl<T, E: Clone, A: ?Sized + FnMut(&Duration, &mut Vec<(Duration, E, T)>)> Logg
     pub fn log_many<I>(&mut self, events: I)
         where I: IntoIterator, I::Item: Into<T>
     {
-        let elapsed = self.time.elapsed() + self.offset;
+        // The time is read only once an event is accepted, as filters may reject most events.
+        let mut elapsed = None;
         for event in events {
-            self.buffer.push((elapsed, self.id.clone(), event.into()));
+            let event = event.into();
+            if let Some(filter) = self.filter.as_mut() {
+                if !filter(&event) { continue; }
+            }
+            let elapsed = *elapsed.get_or_insert_with(|| self.time.elapsed() + self.offset);
+            self.buffer.push((elapsed, self.id.clone(), event));
             if self.buffer.len() == self.buffer.capacity() {
                 // Would call `self.flush()`, but for `RefCell` panic.
                 (self.action)(&elapsed, &mut self.buffer);
@@ -228,6 +324,7 @@ where
             .field("id", &self.id)
             .field("time", &self.time)
             .field("offset", &self.offset)
+            .field("filter", &self.filter.as_ref().map(|_| "FnMut"))
             .field("action", &"FnMut")
             .field("buffer", &self.buffer)
             .finish()
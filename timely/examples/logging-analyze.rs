@@ -0,0 +1,31 @@
+//! Summarizes "timely" log streams captured to files by `EventWriter`.
+//!
+//! Usage: `logging-analyze [--dot FILE] LOG...`
+//!
+//! Prints tables of workers, operators, and channels, and optionally writes the dataflow graph
+//! to FILE in the Graphviz DOT language.
+
+use std::fs::File;
+use std::io::BufReader;
+
+use timely::logging::analysis::Analysis;
+
+fn main() {
+    let mut args = std::env::args().skip(1).peekable();
+    let mut dot = None;
+    if args.peek().map(|arg| arg == "--dot").unwrap_or(false) {
+        args.next();
+        dot = Some(args.next().expect("--dot requires a file name"));
+    }
+
+    let mut analysis = Analysis::new();
+    for path in args {
+        let file = File::open(&path).unwrap_or_else(|error| panic!("failed to open {}: {}", path, error));
+        analysis.read(BufReader::new(file)).unwrap_or_else(|error| panic!("failed to read {}: {}", path, error));
+    }
+
+    print!("{}", analysis.report());
+    if let Some(path) = dot {
+        std::fs::write(&path, analysis.dot()).unwrap_or_else(|error| panic!("failed to write {}: {}", path, error));
+    }
+}
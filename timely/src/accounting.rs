@@ -0,0 +1,232 @@
+//! Accounting of the memory held by dataflows, per operator and per dataflow.
+//!
+//! Components that hold records between invocations of operators report their current size
+//! to an [Account], which is tied to the address of an operator. Each worker keeps track of
+//! its accounts in an [Accounting], which aggregates their usage by operator address and by
+//! dataflow, and which is available through [AsWorker::accounting](crate::worker::AsWorker::accounting).
+//!
+//! Timely accounts for the following components:
+//!
+//! * **channels**, at the address of their target operator: records pushed by a worker to
+//!   itself and not yet pulled, which includes all records in pipeline channels. Records in
+//!   flight to other workers are held by the communication layer, and are not accounted.
+//! * **output buffers**, at the address of their operator: records given to an output session
+//!   and not yet sent downstream.
+//! * **inputs**, at the address of each input operator: records sent to an input handle and not
+//!   yet flushed into the dataflow.
+//!
+//! Output buffers and inputs update their accounts for each record, and are only accounted if the
+//! worker is configured to with [Config::account_buffers](crate::worker::Config::account_buffers).
+//!
+//! Operators account for their own state by obtaining an account from
+//! [AsWorker::account_for](crate::worker::AsWorker::account_for) and updating it as their state
+//! changes. Tees forward each message as it arrives and hold no records, so are not accounted.
+//!
+//! Byte sizes are estimates. Containers are sized by [Container::size_in_bytes] where they support
+//! it, and otherwise by the in-memory size of each record, which does not include memory owned
+//! indirectly by records, for example the contents of a `String`.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Inspect};
+//!
+//! timely::execute_directly(|worker| {
+//!     let mut input = InputHandle::new();
+//!     worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.input_from(&mut input)
+//!              .inspect(|x| println!("seen: {:?}", x));
+//!     });
+//!
+//!     for round in 0 .. 10u64 {
+//!         input.send(round);
+//!     }
+//!     input.advance_to(1);
+//!
+//!     // The records wait in the channel to `inspect`.
+//!     let usage = worker.dataflow_memory_usage();
+//!     assert_eq!(usage[&0].records, 10);
+//! });
+//! ```
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::{Rc, Weak};
+
+use crate::Container;
+
+/// The kind of component an account describes.
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum AccountKind {
+    /// Records in a channel, not yet received by the target operator.
+    Channel,
+    /// Records in an output buffer, not yet sent downstream.
+    Buffer,
+    /// Records in an input handle, not yet introduced to the dataflow.
+    Input,
+    /// State declared by an operator.
+    State,
+}
+
+/// An amount of memory, in records and estimated bytes.
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Copy, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Usage {
+    /// The number of records.
+    pub records: usize,
+    /// The estimated number of bytes.
+    pub bytes: usize,
+}
+
+impl Usage {
+    /// The usage of the records in `container`.
+    pub fn of<C: Container>(container: &C) -> Self {
+        let records = container.len();
+        let bytes = container.size_in_bytes().unwrap_or_else(|| records * record_bytes::<C>());
+        Usage { records, bytes }
+    }
+
+    /// Adds `other` to `self`.
+    pub fn add(&mut self, other: Usage) {
+        self.records += other.records;
+        self.bytes += other.bytes;
+    }
+
+    /// Subtracts `other` from `self`, stopping at zero.
+    pub fn subtract(&mut self, other: Usage) {
+        self.records = self.records.saturating_sub(other.records);
+        self.bytes = self.bytes.saturating_sub(other.bytes);
+    }
+}
+
+/// The estimated number of bytes of each record of `C`.
+pub(crate) fn record_bytes<C: Container>() -> usize {
+    std::mem::size_of::<C::Item<'static>>()
+}
+
+struct AccountInner {
+    address: Vec<usize>,
+    kind: AccountKind,
+    usage: Cell<Usage>,
+}
+
+/// A report of the memory held by one component of an operator.
+///
+/// Clones of an account share its usage, and the account is discarded from its [Accounting]
+/// once all clones are dropped.
+#[derive(Clone)]
+pub struct Account {
+    inner: Rc<AccountInner>,
+}
+
+impl Account {
+    /// The address of the operator the account belongs to.
+    pub fn address(&self) -> &[usize] { &self.inner.address[..] }
+    /// The kind of component the account describes.
+    pub fn kind(&self) -> AccountKind { self.inner.kind }
+    /// The current usage of the account.
+    pub fn usage(&self) -> Usage { self.inner.usage.get() }
+
+    /// Replaces the usage of the account.
+    pub fn set(&self, usage: Usage) {
+        self.inner.usage.set(usage);
+    }
+    /// Increases the usage of the account.
+    pub fn add(&self, usage: Usage) {
+        let mut current = self.inner.usage.get();
+        current.add(usage);
+        self.inner.usage.set(current);
+    }
+    /// Decreases the usage of the account, stopping at zero.
+    pub fn subtract(&self, usage: Usage) {
+        let mut current = self.inner.usage.get();
+        current.subtract(usage);
+        self.inner.usage.set(current);
+    }
+}
+
+impl std::fmt::Debug for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Account")
+            .field("address", &self.inner.address)
+            .field("kind", &self.inner.kind)
+            .field("usage", &self.inner.usage.get())
+            .finish()
+    }
+}
+
+/// The accounts of a worker.
+#[derive(Default)]
+pub struct Accounting {
+    accounts: Vec<Weak<AccountInner>>,
+    /// Usage as of the last call to `changes`.
+    reported: BTreeMap<(Vec<usize>, AccountKind), Usage>,
+}
+
+impl Accounting {
+    /// Allocates a new, empty, accounting.
+    pub fn new() -> Self { Self::default() }
+
+    /// Opens an account of `kind` for the operator at `address`.
+    pub fn account(&mut self, address: &[usize], kind: AccountKind) -> Account {
+        let inner = Rc::new(AccountInner {
+            address: address.to_vec(),
+            kind,
+            usage: Cell::new(Usage::default()),
+        });
+        self.accounts.push(Rc::downgrade(&inner));
+        Account { inner }
+    }
+
+    /// The total usage of open accounts, by operator address and kind.
+    pub fn by_kind(&mut self) -> BTreeMap<(Vec<usize>, AccountKind), Usage> {
+        self.accounts.retain(|account| account.strong_count() > 0);
+        let mut result = BTreeMap::<_, Usage>::new();
+        for account in self.accounts.iter().filter_map(|account| account.upgrade()) {
+            result
+                .entry((account.address.clone(), account.kind))
+                .or_default()
+                .add(account.usage.get());
+        }
+        result
+    }
+
+    /// The total usage of open accounts, by operator address.
+    pub fn by_address(&mut self) -> BTreeMap<Vec<usize>, Usage> {
+        let mut result = BTreeMap::<_, Usage>::new();
+        for ((address, _kind), usage) in self.by_kind() {
+            result.entry(address).or_default().add(usage);
+        }
+        result
+    }
+
+    /// The total usage of open accounts, by dataflow index.
+    pub fn by_dataflow(&mut self) -> BTreeMap<usize, Usage> {
+        let mut result = BTreeMap::<_, Usage>::new();
+        for ((address, _kind), usage) in self.by_kind() {
+            if let Some(dataflow) = address.first() {
+                result.entry(*dataflow).or_default().add(usage);
+            }
+        }
+        result
+    }
+
+    /// Usage by operator address and kind that changed since the previous call.
+    ///
+    /// Accounts that were closed since the previous call are reported with zero usage.
+    pub fn changes(&mut self) -> Vec<((Vec<usize>, AccountKind), Usage)> {
+        let current = self.by_kind();
+        let mut changes = Vec::new();
+        for (key, usage) in current.iter() {
+            if self.reported.get(key) != Some(usage) {
+                changes.push((key.clone(), *usage));
+            }
+        }
+        for key in self.reported.keys() {
+            if !current.contains_key(key) {
+                changes.push((key.clone(), Usage::default()));
+            }
+        }
+        self.reported = current;
+        changes
+    }
+}
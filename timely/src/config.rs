@@ -0,0 +1,265 @@
+//! Structured configuration of timely computations, from files and the environment.
+//!
+//! A [ConfigFile] describes the communication infrastructure, the workers, and the logging
+//! sinks of a computation, and can be deserialized with `serde` from any format. With the
+//! `config` feature enabled, [ConfigFile::from_file] reads TOML or JSON files, and
+//! [Config::from_file](crate::Config::from_file) reads a file, applies any overrides from the
+//! environment, and produces a configuration for `execute`.
+//!
+//! A TOML configuration might read as follows, where all sections and fields are optional.
+//! ```toml
+//! [communication]
+//! threads = 4
+//! process = 0
+//! addresses = ["host0:2101", "host1:2101"]
+//! report = false
+//! zerocopy = false
+//!
+//! [worker]
+//! progress_mode = "demand"
+//! validate_progress = false
+//! step_budget_micros = 1000
+//!
+//! [worker.parameters]
+//! example = 7
+//!
+//! [logging]
+//! timely = "localhost:8000"
+//! communication = "localhost:8001"
+//! ```
+//!
+//! The following environment variables override the corresponding fields:
+//!
+//! | variable                   | field                                |
+//! |----------------------------|--------------------------------------|
+//! | `TIMELY_THREADS`           | `communication.threads`              |
+//! | `TIMELY_PROCESS`           | `communication.process`              |
+//! | `TIMELY_ADDRESSES`         | `communication.addresses`, comma separated |
+//! | `TIMELY_REPORT`            | `communication.report`               |
+//! | `TIMELY_ZEROCOPY`          | `communication.zerocopy`             |
+//! | `TIMELY_PROGRESS_MODE`     | `worker.progress_mode`               |
+//! | `TIMELY_VALIDATE_PROGRESS` | `worker.validate_progress`           |
+//! | `TIMELY_STEP_BUDGET`       | `worker.step_budget_micros`          |
+//! | `TIMELY_OPERATOR_TIME_SLICE` | `worker.operator_time_slice_micros` |
+//! | `TIMELY_OPERATOR_FUEL`     | `worker.operator_fuel`               |
+//! | `TIMELY_MEMORY_LOG_INTERVAL` | `worker.memory_log_interval_micros` |
+//! | `TIMELY_ACCOUNT_BUFFERS`   | `worker.account_buffers`             |
+//! | `TIMELY_WORKER_LOG_ADDR`   | `logging.timely`                     |
+//! | `TIMELY_COMM_LOG_ADDR`     | `logging.communication`              |
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::execute::Config;
+use crate::worker::{Config as WorkerConfig, ProgressMode};
+use crate::CommunicationConfig;
+
+/// A structured description of a timely configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Configuration of the communication infrastructure.
+    pub communication: CommunicationSection,
+    /// Configuration of the workers.
+    pub worker: WorkerSection,
+    /// Configuration of logging sinks.
+    pub logging: LoggingSection,
+}
+
+/// A structured description of the communication infrastructure.
+///
+/// With more than one address, the computation runs as process `process` of a cluster.
+/// Otherwise, it runs in this process with `threads` worker threads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommunicationSection {
+    /// Number of per-process worker threads.
+    pub threads: usize,
+    /// Identity of this process.
+    pub process: usize,
+    /// Addresses of all processes.
+    pub addresses: Vec<String>,
+    /// Verbosely report connection progress.
+    pub report: bool,
+    /// Use zero-copy exchange channels among threads of one process.
+    pub zerocopy: bool,
+}
+
+impl Default for CommunicationSection {
+    fn default() -> Self {
+        CommunicationSection {
+            threads: 1,
+            process: 0,
+            addresses: Vec::new(),
+            report: false,
+            zerocopy: false,
+        }
+    }
+}
+
+/// A structured description of the worker configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkerSection {
+    /// The progress mode, or the default if absent.
+    pub progress_mode: Option<ProgressMode>,
+    /// Whether operator progress statements should be checked for protocol violations.
+    pub validate_progress: bool,
+    /// An optional bound on the time spent scheduling dataflows in each step, in microseconds.
+    pub step_budget_micros: Option<u64>,
+    /// An optional time slice for budgeted operators, in microseconds.
+    pub operator_time_slice_micros: Option<u64>,
+    /// An optional amount of fuel for budgeted operators.
+    pub operator_fuel: Option<usize>,
+    /// An optional interval at which changes in memory usage are logged, in microseconds.
+    pub memory_log_interval_micros: Option<u64>,
+    /// Whether records held by output buffers and input handles are accounted.
+    pub account_buffers: bool,
+    /// Typed parameters, installed in the worker configuration with `set`.
+    pub parameters: BTreeMap<String, Parameter>,
+}
+
+/// A parameter value, installed in the worker configuration with the corresponding type.
+///
+/// Booleans are installed as `bool`, integers as `i64`, floating point numbers as `f64`,
+/// and strings as `String`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Parameter {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A floating point value.
+    Float(f64),
+    /// A string value.
+    String(String),
+}
+
+/// A structured description of logging sinks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSection {
+    /// An address to which each worker sends its timely logging events.
+    pub timely: Option<String>,
+    /// An address to which communication threads send their logging events.
+    pub communication: Option<String>,
+}
+
+impl ConfigFile {
+    /// Reads a configuration from the file at `path`.
+    ///
+    /// Files with a `.json` extension are read as JSON, and all other files as TOML.
+    ///
+    /// This method is only available if the `config` feature is enabled.
+    #[cfg(feature = "config")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<ConfigFile, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        if path.extension().map(|extension| extension == "json").unwrap_or(false) {
+            serde_json::from_str(&text).map_err(|e| format!("invalid configuration in {}: {}", path.display(), e))
+        }
+        else {
+            toml::from_str(&text).map_err(|e| format!("invalid configuration in {}: {}", path.display(), e))
+        }
+    }
+
+    /// Overrides fields with any values set in the environment.
+    pub fn apply_env(&mut self) -> Result<(), String> {
+        self.apply_overrides(std::env::vars())
+    }
+
+    /// Overrides fields with the values of variables named as in the environment.
+    ///
+    /// Variables with other names are ignored.
+    pub fn apply_overrides<I: IntoIterator<Item=(String, String)>>(&mut self, variables: I) -> Result<(), String> {
+        for (name, value) in variables {
+            match name.as_str() {
+                "TIMELY_THREADS" => { self.communication.threads = parse(&name, &value)?; },
+                "TIMELY_PROCESS" => { self.communication.process = parse(&name, &value)?; },
+                "TIMELY_ADDRESSES" => {
+                    self.communication.addresses =
+                    value
+                        .split(',')
+                        .map(|address| address.trim().to_string())
+                        .filter(|address| !address.is_empty())
+                        .collect();
+                },
+                "TIMELY_REPORT" => { self.communication.report = parse(&name, &value)?; },
+                "TIMELY_ZEROCOPY" => { self.communication.zerocopy = parse(&name, &value)?; },
+                "TIMELY_PROGRESS_MODE" => { self.worker.progress_mode = Some(parse(&name, &value)?); },
+                "TIMELY_VALIDATE_PROGRESS" => { self.worker.validate_progress = parse(&name, &value)?; },
+                "TIMELY_STEP_BUDGET" => { self.worker.step_budget_micros = Some(parse(&name, &value)?); },
+                "TIMELY_OPERATOR_TIME_SLICE" => { self.worker.operator_time_slice_micros = Some(parse(&name, &value)?); },
+                "TIMELY_OPERATOR_FUEL" => { self.worker.operator_fuel = Some(parse(&name, &value)?); },
+                "TIMELY_MEMORY_LOG_INTERVAL" => { self.worker.memory_log_interval_micros = Some(parse(&name, &value)?); },
+                "TIMELY_ACCOUNT_BUFFERS" => { self.worker.account_buffers = parse(&name, &value)?; },
+                "TIMELY_WORKER_LOG_ADDR" => { self.logging.timely = Some(value); },
+                "TIMELY_COMM_LOG_ADDR" => { self.logging.communication = Some(value); },
+                _ => { },
+            }
+        }
+        Ok(())
+    }
+
+    /// Produces the configuration described by `self`.
+    pub fn into_config(self) -> Result<Config, String> {
+
+        let ConfigFile { communication, worker, logging } = self;
+
+        let communication = if communication.addresses.len() > 1 {
+            if communication.process >= communication.addresses.len() {
+                return Err(format!("process {} out of range for {} addresses", communication.process, communication.addresses.len()));
+            }
+            CommunicationConfig::Cluster {
+                threads: communication.threads,
+                process: communication.process,
+                addresses: communication.addresses,
+                report: communication.report,
+                log_fn: Box::new(|_| None),
+            }
+        }
+        else if communication.threads > 1 {
+            if communication.zerocopy {
+                CommunicationConfig::ProcessBinary(communication.threads)
+            }
+            else {
+                CommunicationConfig::Process(communication.threads)
+            }
+        }
+        else {
+            CommunicationConfig::Thread
+        };
+
+        let mut config = WorkerConfig::default()
+            .progress_mode(worker.progress_mode.unwrap_or_default())
+            .validate_progress(worker.validate_progress)
+            .step_budget(worker.step_budget_micros.map(Duration::from_micros))
+            .operator_time_slice(worker.operator_time_slice_micros.map(Duration::from_micros))
+            .operator_fuel(worker.operator_fuel)
+            .memory_log_interval(worker.memory_log_interval_micros.map(Duration::from_micros))
+            .account_buffers(worker.account_buffers)
+            .timely_log_address(logging.timely)
+            .communication_log_address(logging.communication);
+        for (key, value) in worker.parameters {
+            match value {
+                Parameter::Bool(value) => config.set(key, value),
+                Parameter::Integer(value) => config.set(key, value),
+                Parameter::Float(value) => config.set(key, value),
+                Parameter::String(value) => config.set(key, value),
+            };
+        }
+
+        Ok(Config { communication, worker: config })
+    }
+}
+
+/// Parses the value of the variable `name`.
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value.trim().parse().map_err(|e| format!("invalid value for {}: {}", name, e))
+}
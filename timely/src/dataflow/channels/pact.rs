@@ -10,6 +10,7 @@
 use std::{fmt::{self, Debug}, marker::PhantomData};
 
 use crate::Container;
+use crate::accounting::{Account, AccountKind, Usage};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 use crate::communication::{Push, Pull, Data};
 use crate::container::PushPartitioned;
@@ -38,8 +39,13 @@ impl<T: 'static, C: Container> ParallelizationContract<T, C> for Pipeline {
     type Puller = LogPuller<T, C, ThreadPuller<Bundle<T, C>>>;
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
         let (pusher, puller) = allocator.pipeline::<Message<T, C>>(identifier, address);
-        (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()),
-         LogPuller::new(puller, allocator.index(), identifier, logging))
+        allocator.graph().borrow_mut().set_pact(identifier, "Pipeline");
+        let account = allocator.accounting().borrow_mut().account(address, AccountKind::Channel);
+        let mut pusher = LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone());
+        let mut puller = LogPuller::new(puller, allocator.index(), identifier, logging);
+        pusher.set_account(account.clone());
+        puller.set_account(account);
+        (pusher, puller)
     }
 }
 
@@ -74,8 +80,14 @@ where
 
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
         let (senders, receiver) = allocator.allocate::<Message<T, C>>(identifier, address);
-        let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
-        (ExchangePusher::new(senders, self.hash_func), LogPuller::new(receiver, allocator.index(), identifier, logging.clone()))
+        allocator.graph().borrow_mut().set_pact(identifier, "Exchange");
+        let account = allocator.accounting().borrow_mut().account(address, AccountKind::Channel);
+        let mut senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
+        // Only records a worker sends to itself remain on this worker until they are received.
+        senders[allocator.index()].set_account(account.clone());
+        let mut receiver = LogPuller::new(receiver, allocator.index(), identifier, logging.clone());
+        receiver.set_account(account);
+        (ExchangePusher::new(senders, self.hash_func), receiver)
     }
 }
 
@@ -95,6 +107,7 @@ pub struct LogPusher<T, C, P: Push<Bundle<T, C>>> {
     target: usize,
     phantom: PhantomData<(T, C)>,
     logging: Option<Logger>,
+    account: Option<Account>,
 }
 
 impl<T, C, P: Push<Bundle<T, C>>> LogPusher<T, C, P> {
@@ -108,8 +121,14 @@ impl<T, C, P: Push<Bundle<T, C>>> LogPusher<T, C, P> {
             target,
             phantom: PhantomData,
             logging,
+            account: None,
         }
     }
+
+    /// Accounts records pushed from here to `account`.
+    pub(crate) fn set_account(&mut self, account: Account) {
+        self.account = Some(account);
+    }
 }
 
 impl<T, C: Container, P: Push<Bundle<T, C>>> Push<Bundle<T, C>> for LogPusher<T, C, P> {
@@ -133,8 +152,13 @@ impl<T, C: Container, P: Push<Bundle<T, C>>> Push<Bundle<T, C>> for LogPusher<T,
                     target: self.target,
                     seq_no: self.counter - 1,
                     length: bundle.data.len(),
+                    bytes: bundle.data.size_in_bytes(),
                 })
             }
+
+            if let Some(account) = self.account.as_ref() {
+                account.add(Usage::of(&bundle.data));
+            }
         }
 
         self.pusher.push(pair);
@@ -149,6 +173,7 @@ pub struct LogPuller<T, C, P: Pull<Bundle<T, C>>> {
     index: usize,
     phantom: PhantomData<(T, C)>,
     logging: Option<Logger>,
+    account: Option<Account>,
 }
 
 impl<T, C, P: Pull<Bundle<T, C>>> LogPuller<T, C, P> {
@@ -160,8 +185,14 @@ impl<T, C, P: Pull<Bundle<T, C>>> LogPuller<T, C, P> {
             index,
             phantom: PhantomData,
             logging,
+            account: None,
         }
     }
+
+    /// Accounts records this worker pulled from itself to `account`.
+    pub(crate) fn set_account(&mut self, account: Account) {
+        self.account = Some(account);
+    }
 }
 
 impl<T, C: Container, P: Pull<Bundle<T, C>>> Pull<Bundle<T, C>> for LogPuller<T, C, P> {
@@ -180,8 +211,15 @@ impl<T, C: Container, P: Pull<Bundle<T, C>>> Pull<Bundle<T, C>> for LogPuller<T,
                     target,
                     seq_no: bundle.seq,
                     length: bundle.data.len(),
+                    bytes: bundle.data.size_in_bytes(),
                 });
             }
+
+            if let Some(account) = self.account.as_ref() {
+                if bundle.from == target {
+                    account.subtract(Usage::of(&bundle.data));
+                }
+            }
         }
 
         result
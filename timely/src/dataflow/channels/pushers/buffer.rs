@@ -1,6 +1,7 @@
 //! Buffering and session mechanisms to provide the appearance of record-at-a-time sending,
 //! with the performance of batched sends.
 
+use crate::accounting::{self, Account, Usage};
 use crate::communication::Push;
 use crate::container::{ContainerBuilder, CapacityContainerBuilder, PushContainer, PushInto};
 use crate::dataflow::channels::{Bundle, Message};
@@ -20,6 +21,8 @@ pub struct Buffer<T, CB, P> {
     builder: CB,
     /// The pusher to send data downstream.
     pusher: P,
+    /// An account of the records in `builder`, if reported.
+    account: Option<Account>,
 }
 
 impl<T, CB: Default, P> Buffer<T, CB, P> {
@@ -29,9 +32,15 @@ impl<T, CB: Default, P> Buffer<T, CB, P> {
             time: None,
             builder: Default::default(),
             pusher,
+            account: None,
         }
     }
 
+    /// Accounts records held by the buffer to `account`.
+    pub(crate) fn set_account(&mut self, account: Account) {
+        self.account = Some(account);
+    }
+
     /// Returns a reference to the inner `P: Push` type.
     ///
     /// This is currently used internally, and should not be used without some care.
@@ -88,6 +97,7 @@ impl<T, CB: ContainerBuilder, P: Push<Bundle<T, CB::Container>>> Buffer<T, CB, P
     #[inline]
     fn extract(&mut self) {
         while let Some(container) = self.builder.extract() {
+            if let Some(account) = self.account.as_ref() { account.subtract(Usage::of(container)); }
             let time = self.time.as_ref().unwrap().clone();
             Message::push_at(container, time, &mut self.pusher);
         }
@@ -100,11 +110,13 @@ impl<T, CB: ContainerBuilder, P: Push<Bundle<T, CB::Container>>> Buffer<T, CB, P
             let time = self.time.as_ref().unwrap().clone();
             Message::push_at(container, time, &mut self.pusher);
         }
+        if let Some(account) = self.account.as_ref() { account.set(Usage::default()); }
     }
 
     /// Gives an entire container at the current time.
     fn give_container(&mut self, container: &mut CB::Container) {
         if !container.is_empty() {
+            if let Some(account) = self.account.as_ref() { account.add(Usage::of(container)); }
             self.builder.push_container(container);
             self.extract();
         }
@@ -119,6 +131,9 @@ where
     // Push a single item into the builder. Internal method for use by `Session`.
     #[inline]
     fn give<D: PushInto<CB::Container>>(&mut self, data: D) {
+        if let Some(account) = self.account.as_ref() {
+            account.add(Usage { records: 1, bytes: accounting::record_bytes::<CB::Container>() });
+        }
         self.builder.push(data);
         self.extract();
     }
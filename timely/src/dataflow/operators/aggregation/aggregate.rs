@@ -2,10 +2,14 @@
 use std::hash::Hash;
 use std::collections::HashMap;
 
+use abomonation::Abomonation;
+
 use crate::{Data, ExchangeData};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::checkpoint::{Checkpoint, UnaryFrontierCheckpoint};
 use crate::dataflow::channels::pact::Exchange;
+use crate::order::PartialOrder;
 
 /// Generic intra-timestamp aggregation
 ///
@@ -65,6 +69,48 @@ pub trait Aggregate<S: Scope, K: ExchangeData+Hash, V: ExchangeData> {
         fold: F,
         emit: E,
         hash: H) -> Stream<S, R> where S::Timestamp: Eq;
+
+    /// Aggregates data of the form `(key, val)` as `aggregate`, with aggregates registered with `checkpoint`.
+    ///
+    /// While a snapshot at time `t` is due, the aggregates of times less than `t` are held back
+    /// rather than produced as their times complete. Once all input at times less than `t` has
+    /// arrived, the snapshot records these aggregates, and they are produced. When the operator
+    /// is reconstructed after `checkpoint` has been restored, the recorded aggregates are restored
+    /// and produced again, and so checkpointed operators downstream of the aggregate receive them
+    /// twice.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::Aggregate;
+    /// use timely::dataflow::operators::checkpoint::Checkpoint;
+    ///
+    /// let directory = std::env::temp_dir().join(format!("timely-checkpoint-aggregate-{}", std::process::id()));
+    /// timely::execute_directly(move |worker| {
+    ///     let checkpoint = Checkpoint::new(worker, &directory);
+    ///     checkpoint.snapshot_at(1);
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10u64).to_stream(scope)
+    ///             .map(|x| (x % 2, x))
+    ///             .aggregate_checkpointed(
+    ///                 |_key, val, agg| { *agg += val; },
+    ///                 |key, agg: u64| (key, agg),
+    ///                 |key| *key,
+    ///                 &checkpoint,
+    ///             )
+    ///             .inspect(|x| assert!(*x == (0, 20) || *x == (1, 25)));
+    ///     });
+    ///     while worker.step() { }
+    ///     assert_eq!(checkpoint.completed(), Some(1));
+    ///     std::fs::remove_dir_all(&directory).ok();
+    /// });
+    /// ```
+    fn aggregate_checkpointed<R: Data, D: Default+Abomonation+Clone+'static, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        emit: E,
+        hash: H,
+        checkpoint: &Checkpoint<S::Timestamp>) -> Stream<S, R> where S::Timestamp: Eq+Abomonation, K: Abomonation;
 }
 
 impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for Stream<S, (K, V)> {
@@ -102,4 +148,71 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for
         })
 
     }
+
+    fn aggregate_checkpointed<R: Data, D: Default+Abomonation+Clone+'static, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        emit: E,
+        hash: H,
+        checkpoint: &Checkpoint<S::Timestamp>) -> Stream<S, R> where S::Timestamp: Eq+Abomonation, K: Abomonation {
+
+        let mut vector = Vec::new();
+        self.unary_frontier_checkpoint(Exchange::new(move |&(ref k, _)| hash(k)), "Aggregate", checkpoint, move |capability, _info, registration| {
+
+            // aggregates and capabilities for incomplete times, restored from any snapshot.
+            let mut aggregates = HashMap::new();
+            let mut capabilities = HashMap::new();
+            let restored: Option<Vec<(S::Timestamp, Vec<(K, D)>)>> = registration.restored();
+            for (time, aggs) in restored.unwrap_or_default() {
+                capabilities.insert(time.clone(), capability.delayed(&time));
+                aggregates.insert(time, aggs.into_iter().collect::<HashMap<_,_>>());
+            }
+            drop(capability);
+
+            move |input, output| {
+
+                // read each input, fold into aggregates
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    let agg_time = aggregates.entry(time.time().clone()).or_insert_with(HashMap::new);
+                    for (key, val) in vector.drain(..) {
+                        let agg = agg_time.entry(key.clone()).or_insert_with(Default::default);
+                        fold(&key, val, agg);
+                    }
+                    capabilities.entry(time.time().clone()).or_insert_with(|| time.retain());
+                });
+
+                // record aggregates before a requested snapshot time, once all such input has arrived.
+                if let Some(snapshot) = registration.due() {
+                    if !input.frontier().less_than(&snapshot) {
+                        let state = aggregates
+                            .iter()
+                            .filter(|(time, _)| time.less_than(&snapshot))
+                            .map(|(time, aggs)| (time.clone(), aggs.iter().map(|(k, d)| (k.clone(), d.clone())).collect::<Vec<_>>()))
+                            .collect::<Vec<_>>();
+                        registration.write(&state);
+                    }
+                }
+
+                // pop completed aggregates, holding back those before a snapshot yet to be written.
+                let held = registration.due();
+                let mut complete = capabilities
+                    .keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .filter(|time| held.as_ref().map(|snapshot| !time.less_than(snapshot)).unwrap_or(true))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                complete.sort();
+                for time in complete {
+                    let capability = capabilities.remove(&time).unwrap();
+                    if let Some(aggs) = aggregates.remove(&time) {
+                        let mut session = output.session(&capability);
+                        for (key, agg) in aggs {
+                            session.give(emit(key, agg));
+                        }
+                    }
+                }
+            }
+        })
+    }
 }
@@ -2,10 +2,14 @@
 use std::hash::Hash;
 use std::collections::HashMap;
 
+use abomonation::Abomonation;
+
 use crate::{Data, ExchangeData};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::checkpoint::{Checkpoint, UnaryFrontierCheckpoint};
 use crate::dataflow::channels::pact::Exchange;
+use crate::order::PartialOrder;
 
 /// Generic state-transition machinery: each key has a state, and receives a sequence of events.
 /// Events are applied in time-order, but no other promises are made. Each state transition can
@@ -52,6 +56,46 @@ pub trait StateMachine<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> {
         F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
         H: Fn(&K)->u64+'static,                     // "hash" function for keys
     >(&self, fold: F, hash: H) -> Stream<S, R> where S::Timestamp : Hash+Eq ;
+
+    /// Tracks a state for each presented key as `state_machine`, with states registered with `checkpoint`.
+    ///
+    /// Unlike `state_machine`, events are applied only once their time is complete, in the order
+    /// of their times. A snapshot at time `t` records the states after applying all events at
+    /// times less than `t`, and any events at such times not yet applied. These are restored when
+    /// the operator is reconstructed after `checkpoint` has been restored.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::StateMachine;
+    /// use timely::dataflow::operators::checkpoint::Checkpoint;
+    ///
+    /// let directory = std::env::temp_dir().join(format!("timely-checkpoint-state-machine-{}", std::process::id()));
+    /// timely::execute_directly(move |worker| {
+    ///     let checkpoint = Checkpoint::new(worker, &directory);
+    ///     checkpoint.snapshot_at(1);
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10u64).to_stream(scope)
+    ///             .map(|x| (x % 2, x))
+    ///             .state_machine_checkpointed(
+    ///                 |key, val, agg: &mut u64| { *agg += val; (false, Some((*key, *agg))) },
+    ///                 |key| *key,
+    ///                 &checkpoint,
+    ///             )
+    ///             .inspect(|x| println!("state: {:?}", x));
+    ///     });
+    ///     while worker.step() { }
+    ///     assert_eq!(checkpoint.completed(), Some(1));
+    ///     std::fs::remove_dir_all(&directory).ok();
+    /// });
+    /// ```
+    fn state_machine_checkpointed<
+        R: Data,                                    // output type
+        D: Default+Abomonation+Clone+'static,       // per-key state (data)
+        I: IntoIterator<Item=R>,                    // type of output iterator
+        F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
+        H: Fn(&K)->u64+'static,                     // "hash" function for keys
+    >(&self, fold: F, hash: H, checkpoint: &Checkpoint<S::Timestamp>) -> Stream<S, R> where S::Timestamp : Hash+Eq+Abomonation, K: Abomonation, V: Abomonation ;
 }
 
 impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> StateMachine<S, K, V> for Stream<S, (K, V)> {
@@ -110,4 +154,89 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> StateMachine<S, K, V> f
             });
         })
     }
+
+    fn state_machine_checkpointed<
+            R: Data,                                    // output type
+            D: Default+Abomonation+Clone+'static,       // per-key state (data)
+            I: IntoIterator<Item=R>,                    // type of output iterator
+            F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
+            H: Fn(&K)->u64+'static,                     // "hash" function for keys
+        >(&self, fold: F, hash: H, checkpoint: &Checkpoint<S::Timestamp>) -> Stream<S, R> where S::Timestamp : Hash+Eq+Abomonation, K: Abomonation, V: Abomonation {
+
+        let mut vector = Vec::new();
+
+        self.unary_frontier_checkpoint(Exchange::new(move |&(ref k, _)| hash(k)), "StateMachine", checkpoint, move |capability, _info, registration| {
+
+            let mut pending: HashMap<_, Vec<(K, V)>> = HashMap::new();   // times -> (keys -> state)
+            let mut states = HashMap::new();    // keys -> state
+            let mut capabilities = HashMap::new();
+
+            // restore states and unapplied events from any snapshot.
+            let restored: Option<(Vec<(K, D)>, Vec<(S::Timestamp, Vec<(K, V)>)>)> = registration.restored();
+            if let Some((restored_states, restored_pending)) = restored {
+                states.extend(restored_states);
+                for (time, pend) in restored_pending {
+                    capabilities.insert(time.clone(), capability.delayed(&time));
+                    pending.insert(time, pend);
+                }
+            }
+            drop(capability);
+
+            move |input, output| {
+
+                // stash each input, retaining a capability for its time.
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    pending.entry(time.time().clone()).or_insert_with(Vec::new).extend(vector.drain(..));
+                    capabilities.entry(time.time().clone()).or_insert_with(|| time.retain());
+                });
+
+                // apply events at complete times, in order of their times.
+                let mut ready = capabilities.keys().filter(|time| !input.frontier().less_equal(time)).cloned().collect::<Vec<_>>();
+                ready.sort();
+                for time in ready {
+                    // record states before applying events at or beyond a requested snapshot time.
+                    if let Some(snapshot) = registration.due() {
+                        if snapshot.less_equal(&time) {
+                            registration.write(&snapshot_state(&states, &pending, &snapshot));
+                        }
+                    }
+                    let capability = capabilities.remove(&time).unwrap();
+                    if let Some(pend) = pending.remove(&time) {
+                        let mut session = output.session(&capability);
+                        for (key, val) in pend {
+                            let (remove, output) = {
+                                let state = states.entry(key.clone()).or_insert_with(Default::default);
+                                fold(&key, val, state)
+                            };
+                            if remove { states.remove(&key); }
+                            session.give_iterator(output.into_iter());
+                        }
+                    }
+                }
+
+                // record states once all input before a requested snapshot time has arrived.
+                if let Some(snapshot) = registration.due() {
+                    if !input.frontier().less_than(&snapshot) {
+                        registration.write(&snapshot_state(&states, &pending, &snapshot));
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The checkpointed state of `state_machine_checkpointed` for a snapshot at `time`.
+fn snapshot_state<T: PartialOrder+Clone, K: Clone, V: Clone, D: Clone>(
+    states: &HashMap<K, D>,
+    pending: &HashMap<T, Vec<(K, V)>>,
+    time: &T,
+) -> (Vec<(K, D)>, Vec<(T, Vec<(K, V)>)>) {
+    let states = states.iter().map(|(k, d)| (k.clone(), d.clone())).collect();
+    let pending = pending
+        .iter()
+        .filter(|(t, _)| t.less_than(time))
+        .map(|(t, pend)| (t.clone(), pend.clone()))
+        .collect();
+    (states, pending)
 }
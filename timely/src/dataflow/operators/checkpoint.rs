@@ -0,0 +1,429 @@
+//! Checkpointing and restoring operator state.
+//!
+//! A [Checkpoint] coordinates snapshots of the state of registered operators at one worker.
+//! A snapshot is requested for a time `t` with [Checkpoint::snapshot_at], and each registered
+//! operator writes its state once it reflects exactly the input at times strictly less than `t`,
+//! which is typically when its input frontier passes `t`. Once all registered operators have
+//! written their state the snapshot is complete at the worker, and once it is complete at all
+//! workers it can be restored from.
+//!
+//! All workers should request the same snapshots, much as they construct the same dataflows.
+//! A restarted computation calls [Checkpoint::restore] before constructing its dataflows, which
+//! locates the snapshot with the greatest time completed by all workers; operators then reload
+//! their state as they register, and the computation should resume its inputs from the restored
+//! time.
+//!
+//! Snapshots are stored in a directory with the following layout, where each snapshot is
+//! identified by a hash of its time, so that workers agree on it without coordination, and the
+//! state of each operator is identified by its worker-unique global index:
+//! ```text
+//! directory/snapshot-<hash>/worker-<index>/operator-<global>
+//! directory/snapshot-<hash>/worker-<index>.complete
+//! ```
+//! Snapshots are written with `abomonation`, and must be restored by the same program. A snapshot
+//! at a time already in the directory replaces it, and is not restored until it is complete again.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//! use timely::dataflow::operators::aggregation::StateMachine;
+//! use timely::dataflow::operators::checkpoint::Checkpoint;
+//!
+//! let directory = std::env::temp_dir().join(format!("timely-checkpoint-doc-{}", std::process::id()));
+//!
+//! timely::execute_directly(move |worker| {
+//!
+//!     let checkpoint = Checkpoint::<u64>::new(worker, &directory);
+//!     let resume = checkpoint.restore().expect("failed to read checkpoints").unwrap_or(0);
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .map(|x: u64| (x % 2, x))
+//!              .state_machine_checkpointed(
+//!                  |_key, val, agg: &mut u64| { *agg += val; (false, Some(*agg)) },
+//!                  |key| *key,
+//!                  &checkpoint,
+//!              )
+//!              .probe()
+//!     });
+//!
+//!     checkpoint.snapshot_at(5);
+//!     input.advance_to(resume);
+//!     for round in resume .. 10 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//!     assert_eq!(checkpoint.completed(), Some(5));
+//!     std::fs::remove_dir_all(&directory).ok();
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use abomonation::Abomonation;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::ParallelizationContract;
+use crate::dataflow::channels::pushers::Tee;
+use crate::container::CapacityContainerBuilder;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::{Operator, OperatorInfo, FrontieredInputHandle, OutputHandle};
+use crate::progress::Timestamp;
+use crate::worker::AsWorker;
+
+/// Coordinates snapshots of registered operator state at one worker.
+pub struct Checkpoint<T> {
+    inner: Rc<RefCell<CheckpointInner<T>>>,
+}
+
+impl<T> Clone for Checkpoint<T> {
+    fn clone(&self) -> Self {
+        Checkpoint { inner: self.inner.clone() }
+    }
+}
+
+struct CheckpointInner<T> {
+    directory: PathBuf,
+    index: usize,
+    peers: usize,
+    /// The snapshot restored from, by identifier and time.
+    restored: Option<(u64, T)>,
+    /// The requested snapshot, by identifier and time, and the operators that have written it.
+    pending: Option<(u64, T, BTreeSet<usize>)>,
+    /// The global indices of registered operators.
+    registered: BTreeSet<usize>,
+    /// The time of the most recent snapshot completed at this worker.
+    completed: Option<T>,
+}
+
+impl<T: Timestamp+Abomonation> CheckpointInner<T> {
+
+    fn snapshot_path(&self, id: u64) -> PathBuf {
+        self.directory.join(format!("snapshot-{:016x}", id))
+    }
+
+    fn operator_path(&self, id: u64, operator: usize) -> PathBuf {
+        self.snapshot_path(id)
+            .join(format!("worker-{}", self.index))
+            .join(format!("operator-{}", operator))
+    }
+
+    fn marker_path(&self, id: u64, worker: usize) -> PathBuf {
+        self.snapshot_path(id).join(format!("worker-{}.complete", worker))
+    }
+
+    /// Completes the pending snapshot, if all registered operators have written it.
+    fn try_complete(&mut self) {
+        let complete = self.pending
+            .as_ref()
+            .map(|(_, _, written)| self.registered.is_subset(written))
+            .unwrap_or(false);
+        if complete {
+            let (id, time, _) = self.pending.take().unwrap();
+            write_atomic(&self.marker_path(id, self.index), &time)
+                .unwrap_or_else(|e| panic!("failed to complete checkpoint {:?}: {}", time, e));
+            self.completed = Some(time);
+        }
+    }
+}
+
+impl<T: Timestamp+Abomonation> Checkpoint<T> {
+
+    /// Creates a checkpoint coordinator for `worker`, storing snapshots in `directory`.
+    pub fn new<A: AsWorker, P: AsRef<Path>>(worker: &A, directory: P) -> Self {
+        Checkpoint {
+            inner: Rc::new(RefCell::new(CheckpointInner {
+                directory: directory.as_ref().to_path_buf(),
+                index: worker.index(),
+                peers: worker.peers(),
+                restored: None,
+                pending: None,
+                registered: BTreeSet::new(),
+                completed: None,
+            }))
+        }
+    }
+
+    /// Locates the snapshot with the greatest time completed by all workers, and returns its time.
+    ///
+    /// Operators registered after this call reload their state from the snapshot, and the
+    /// computation should resume its inputs from the returned time. Returns `None` if there
+    /// is no complete snapshot, in which case the computation should start from scratch.
+    pub fn restore(&self) -> io::Result<Option<T>> {
+        let mut inner = self.inner.borrow_mut();
+        let mut latest: Option<(u64, T)> = None;
+        for id in snapshot_identifiers(&inner.directory) {
+            if (0 .. inner.peers).all(|worker| inner.marker_path(id, worker).exists()) {
+                let time = read_state::<T>(&inner.marker_path(id, inner.index))?;
+                if latest.as_ref().map(|(_, latest)| latest < &time).unwrap_or(true) {
+                    latest = Some((id, time));
+                }
+            }
+        }
+        if let Some((id, time)) = latest {
+            inner.restored = Some((id, time.clone()));
+            inner.completed = Some(time.clone());
+            Ok(Some(time))
+        }
+        else {
+            Ok(None)
+        }
+    }
+
+    /// Requests a snapshot of all registered operators at `time`.
+    ///
+    /// Returns `false` without effect if a snapshot is already pending. Panics if an earlier
+    /// snapshot at `time` cannot be invalidated.
+    pub fn snapshot_at(&self, time: T) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        if inner.pending.is_some() {
+            false
+        }
+        else {
+            let id = snapshot_identifier(&time);
+            // An earlier snapshot at this time is no longer complete at this worker.
+            let marker = inner.marker_path(id, inner.index);
+            if let Err(e) = fs::remove_file(&marker) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    panic!("failed to invalidate {}: {}", marker.display(), e);
+                }
+            }
+            inner.pending = Some((id, time, BTreeSet::new()));
+            inner.try_complete();
+            true
+        }
+    }
+
+    /// The time of the pending snapshot, if any.
+    pub fn pending(&self) -> Option<T> {
+        self.inner.borrow().pending.as_ref().map(|(_, time, _)| time.clone())
+    }
+
+    /// The time of the most recent snapshot completed at this worker, or restored from.
+    pub fn completed(&self) -> Option<T> {
+        self.inner.borrow().completed.clone()
+    }
+
+    /// Registers the operator with global index `operator` as holding state of type `S`.
+    ///
+    /// The returned registration reloads the operator's state from a restored snapshot, and is
+    /// used by the operator to write its state when a snapshot is due. Dropping the registration
+    /// deregisters the operator, so that later snapshots do not await it, and abandons a pending
+    /// snapshot the operator has not written, as it would lack the operator's state.
+    pub fn register<S: Abomonation+Clone>(&self, operator: usize) -> Registration<T, S> {
+        self.inner.borrow_mut().registered.insert(operator);
+        Registration {
+            checkpoint: self.clone(),
+            operator,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// An operator's handle for reading and writing its state in snapshots.
+pub struct Registration<T: Timestamp+Abomonation, S> {
+    checkpoint: Checkpoint<T>,
+    operator: usize,
+    phantom: PhantomData<S>,
+}
+
+impl<T: Timestamp+Abomonation, S: Abomonation+Clone> Registration<T, S> {
+
+    /// The operator state in the restored snapshot, if any.
+    ///
+    /// Panics if the snapshot contains state for the operator which cannot be read.
+    pub fn restored(&self) -> Option<S> {
+        let inner = self.checkpoint.inner.borrow();
+        let (id, _) = inner.restored.as_ref()?;
+        let path = inner.operator_path(*id, self.operator);
+        if path.exists() {
+            let state = read_state::<S>(&path)
+                .unwrap_or_else(|e| panic!("failed to restore {}: {}", path.display(), e));
+            Some(state)
+        }
+        else {
+            None
+        }
+    }
+
+    /// The time of a snapshot that this operator has yet to write, if any.
+    ///
+    /// The operator should write its state with `write` once the state reflects exactly its
+    /// input at times strictly less than the returned time.
+    pub fn due(&self) -> Option<T> {
+        let inner = self.checkpoint.inner.borrow();
+        inner.pending
+            .as_ref()
+            .filter(|(_, _, written)| !written.contains(&self.operator))
+            .map(|(_, time, _)| time.clone())
+    }
+
+    /// Writes `state` as the operator's state in the pending snapshot.
+    ///
+    /// Has no effect if no snapshot is due. Panics if the state cannot be written.
+    pub fn write(&self, state: &S) {
+        let mut inner = self.checkpoint.inner.borrow_mut();
+        if let Some((id, _, written)) = inner.pending.as_ref() {
+            if !written.contains(&self.operator) {
+                let path = inner.operator_path(*id, self.operator);
+                write_atomic(&path, state)
+                    .unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+                inner.pending.as_mut().unwrap().2.insert(self.operator);
+                inner.try_complete();
+            }
+        }
+    }
+}
+
+impl<T: Timestamp+Abomonation, S> Drop for Registration<T, S> {
+    fn drop(&mut self) {
+        let mut inner = self.checkpoint.inner.borrow_mut();
+        inner.registered.remove(&self.operator);
+        let unwritten = inner.pending.as_ref().map(|(_, _, written)| !written.contains(&self.operator)).unwrap_or(false);
+        if unwritten {
+            inner.pending = None;
+        }
+    }
+}
+
+/// A variant of `unary_frontier` for operators with checkpointed state.
+pub trait UnaryFrontierCheckpoint<G: Scope, D1: Data> {
+    /// Creates a new dataflow operator whose state is registered with `checkpoint`.
+    ///
+    /// This method behaves as `unary_frontier`, and additionally provides the constructor with
+    /// the operator's [Registration]. The constructor can reload the operator's state with
+    /// `restored()`, and the operator logic should write its state with `write()` once a
+    /// snapshot is `due()` and its input frontier has passed the snapshot time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::operators::checkpoint::{Checkpoint, UnaryFrontierCheckpoint};
+    ///
+    /// let directory = std::env::temp_dir().join(format!("timely-checkpoint-unary-{}", std::process::id()));
+    /// timely::execute_directly(move |worker| {
+    ///     let checkpoint = Checkpoint::<u64>::new(worker, &directory);
+    ///     checkpoint.snapshot_at(5);
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10u64)
+    ///             .to_stream(scope)
+    ///             .unary_frontier_checkpoint(Pipeline, "Sum", &checkpoint, |_capability, _info, registration| {
+    ///                 // A running sum of all input, restored from any snapshot.
+    ///                 let mut sum: u64 = registration.restored().unwrap_or(0);
+    ///                 move |input, output| {
+    ///                     input.for_each(|time, data| {
+    ///                         sum += data.iter().sum::<u64>();
+    ///                         output.session(&time).give(sum);
+    ///                     });
+    ///                     if let Some(time) = registration.due() {
+    ///                         if !input.frontier().less_than(&time) {
+    ///                             registration.write(&sum);
+    ///                         }
+    ///                     }
+    ///                 }
+    ///             })
+    ///             .inspect(|x| println!("sum: {:?}", x));
+    ///     });
+    ///     while worker.step() { }
+    ///     assert_eq!(checkpoint.completed(), Some(5));
+    ///     std::fs::remove_dir_all(&directory).ok();
+    /// });
+    /// ```
+    fn unary_frontier_checkpoint<D2, S, B, L, P>(&self, pact: P, name: &str, checkpoint: &Checkpoint<G::Timestamp>, constructor: B) -> Stream<G, D2>
+    where
+        D2: Data,
+        S: Abomonation+Clone+'static,
+        G::Timestamp: Abomonation,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo, Registration<G::Timestamp, S>) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, Vec<D2>>>)+'static,
+        P: ParallelizationContract<G::Timestamp, Vec<D1>>;
+}
+
+impl<G: Scope, D1: Data> UnaryFrontierCheckpoint<G, D1> for Stream<G, D1> {
+    fn unary_frontier_checkpoint<D2, S, B, L, P>(&self, pact: P, name: &str, checkpoint: &Checkpoint<G::Timestamp>, constructor: B) -> Stream<G, D2>
+    where
+        D2: Data,
+        S: Abomonation+Clone+'static,
+        G::Timestamp: Abomonation,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo, Registration<G::Timestamp, S>) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, Vec<D2>>>)+'static,
+        P: ParallelizationContract<G::Timestamp, Vec<D1>>
+    {
+        let checkpoint = checkpoint.clone();
+        self.unary_frontier::<CapacityContainerBuilder<Vec<D2>>,_,_,_>(pact, name, move |capability, info| {
+            let registration = checkpoint.register(info.global_id);
+            constructor(capability, info, registration)
+        })
+    }
+}
+
+/// The identifier of the snapshot at `time`, which all workers compute alike.
+fn snapshot_identifier<T: Hash>(time: &T) -> u64 {
+    let mut hasher = Fnv(0xcbf29ce484222325);
+    time.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The FNV-1a hash, which unlike `DefaultHasher` is specified and so stable across releases.
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 { self.0 }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// The identifiers of snapshot directories in `directory`.
+fn snapshot_identifiers(directory: &Path) -> Vec<u64> {
+    fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    entry.file_name()
+                        .to_str()
+                        .and_then(|name| name.strip_prefix("snapshot-"))
+                        .and_then(|id| u64::from_str_radix(id, 16).ok())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Writes `state` to `path`, through a temporary file so that partial writes are never observed.
+fn write_atomic<S: Abomonation>(path: &Path, state: &S) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut bytes = Vec::new();
+    unsafe { ::abomonation::encode(state, &mut bytes)?; }
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, bytes)?;
+    fs::rename(&temporary, path)
+}
+
+/// Reads state written by `write_atomic` from `path`.
+fn read_state<S: Abomonation+Clone>(path: &Path) -> io::Result<S> {
+    let mut bytes = fs::read(path)?;
+    match unsafe { ::abomonation::decode::<S>(&mut bytes[..]) } {
+        Some((state, rest)) if rest.is_empty() => Ok(state.clone()),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "malformed checkpoint state")),
+    }
+}
@@ -198,3 +198,365 @@ pub mod binary {
         }
     }
 }
+
+/// A binary event pusher and iterator over rotated files.
+///
+/// A `RotatingWriter` writes events in the format of `EventWriter` to a sequence of segment files
+/// in a directory, named `{prefix}.{worker}.{segment}.log`, and starts a new segment once the
+/// current one would exceed a size or has been open for a time. Each segment begins with a
+/// [SegmentHeader] describing its contents, and the number of segments retained can be bounded,
+/// which bounds the space the log occupies. A `RotatingReader` reads the segments of one worker
+/// with a prefix in order, and can follow a log as it is written. The workers of a computation may
+/// share a directory and prefix, as the segments of each are named by its index.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{Capture, ToStream, Inspect};
+/// use timely::dataflow::operators::capture::{Replay, RotatingReader, RotatingWriter};
+///
+/// let directory = std::env::temp_dir().join(format!("timely-rotating-doc-{}", std::process::id()));
+///
+/// let directory2 = directory.clone();
+/// timely::execute(timely::Config::thread(), move |worker| {
+///     let directory = &directory2;
+///     let writer = RotatingWriter::new(directory, "numbers", worker.index()).max_bytes(1 << 10);
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..10u64).to_stream(scope).capture_into(writer);
+///     });
+///     while worker.has_dataflows() { worker.step(); }
+///
+///     let reader = RotatingReader::<u64, Vec<u64>>::open(directory, "numbers", worker.index()).unwrap();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         Some(reader)
+///             .replay_into(scope)
+///             .inspect(|x| println!("replayed: {:?}", x));
+///     });
+/// }).unwrap();
+///
+/// std::fs::remove_dir_all(&directory).unwrap();
+/// ```
+pub mod rotating {
+
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    use abomonation::Abomonation;
+    use super::{Event, EventPusher, EventIterator};
+
+    /// Bytes identifying a segment file.
+    const MAGIC: &[u8; 8] = b"TDFLOG\x00\x01";
+
+    /// The description at the start of each segment.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct SegmentHeader {
+        /// The name of the type of the events in the segment.
+        pub event_type: String,
+        /// The index of the worker that wrote the segment.
+        pub worker: usize,
+        /// The version of timely that wrote the segment.
+        pub version: String,
+        /// The position of the segment in the sequence of segments.
+        pub segment: u64,
+    }
+
+    impl SegmentHeader {
+        /// The header of segment `segment` of events of type `Event<T, C>` written by `worker`.
+        pub fn new<T, C>(worker: usize, segment: u64) -> Self {
+            SegmentHeader {
+                event_type: std::any::type_name::<Event<T, C>>().to_owned(),
+                worker,
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+                segment,
+            }
+        }
+
+        /// Writes the header, as its magic bytes, the length of its text, and its text.
+        pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+            let text = format!(
+                "event={}\nworker={}\nversion={}\nsegment={}\n",
+                self.event_type, self.worker, self.version, self.segment,
+            );
+            let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + text.len());
+            bytes.extend_from_slice(MAGIC);
+            bytes.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+            writer.write_all(&bytes)
+        }
+
+        /// Reads a header written by `write_to`.
+        pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+            let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+            let mut magic = [0u8; 8];
+            reader.read_exact(&mut magic)?;
+            if &magic != MAGIC { return Err(invalid("not a timely log segment")); }
+            let mut length = [0u8; 4];
+            reader.read_exact(&mut length)?;
+            let mut text = vec![0u8; u32::from_le_bytes(length) as usize];
+            reader.read_exact(&mut text)?;
+            let text = String::from_utf8(text).map_err(|_| invalid("segment header is not UTF-8"))?;
+
+            let (mut event_type, mut worker, mut version, mut segment) = (None, None, None, None);
+            for line in text.lines() {
+                match line.split_once('=') {
+                    Some(("event", value)) => event_type = Some(value.to_owned()),
+                    Some(("worker", value)) => worker = value.parse().ok(),
+                    Some(("version", value)) => version = Some(value.to_owned()),
+                    Some(("segment", value)) => segment = value.parse().ok(),
+                    // Unknown fields are ignored, to admit extensions.
+                    _ => { },
+                }
+            }
+            match (event_type, worker, version, segment) {
+                (Some(event_type), Some(worker), Some(version), Some(segment)) => {
+                    Ok(SegmentHeader { event_type, worker, version, segment })
+                },
+                _ => Err(invalid("incomplete segment header")),
+            }
+        }
+    }
+
+    /// The segment files of `worker` in `directory` with prefix `prefix`, in order.
+    pub fn segments(directory: &Path, prefix: &str, worker: usize) -> io::Result<Vec<(u64, PathBuf)>> {
+        let worker = format!("{}.", worker);
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let segment = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(prefix))
+                .and_then(|name| name.strip_prefix('.'))
+                .and_then(|name| name.strip_prefix(worker.as_str()))
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|number| number.parse().ok());
+            if let Some(segment) = segment {
+                segments.push((segment, path));
+            }
+        }
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// An `EventPusher<T, C>` writing to rotated segment files.
+    ///
+    /// Segments are created as events are pushed, continuing after any existing segments with the
+    /// same prefix and worker. An event is never split between segments, and so a segment exceeds the size
+    /// bound only if it holds a single event larger than the bound.
+    pub struct RotatingWriter<T, C> {
+        directory: PathBuf,
+        prefix: String,
+        worker: usize,
+        max_bytes: Option<u64>,
+        max_age: Option<Duration>,
+        max_segments: Option<usize>,
+        /// The current segment, its number, its size, and when it was created.
+        current: Option<(File, u64, u64, Instant)>,
+        buffer: Vec<u8>,
+        phant: ::std::marker::PhantomData<(T, C)>,
+    }
+
+    impl<T, C> RotatingWriter<T, C> {
+        /// Allocates a writer of segments of `worker`'s events, named by `prefix` in `directory`.
+        ///
+        /// The directory is created when the first event is pushed, if it does not exist.
+        pub fn new<P: AsRef<Path>>(directory: P, prefix: &str, worker: usize) -> Self {
+            Self {
+                directory: directory.as_ref().to_owned(),
+                prefix: prefix.to_owned(),
+                worker,
+                max_bytes: None,
+                max_age: None,
+                max_segments: None,
+                current: None,
+                buffer: Vec::new(),
+                phant: ::std::marker::PhantomData,
+            }
+        }
+
+        /// Starts a new segment before a segment would exceed `bytes` bytes.
+        pub fn max_bytes(mut self, bytes: u64) -> Self {
+            self.max_bytes = Some(bytes);
+            self
+        }
+
+        /// Starts a new segment once a segment has been written to for `age`.
+        pub fn max_age(mut self, age: Duration) -> Self {
+            self.max_age = Some(age);
+            self
+        }
+
+        /// Removes the oldest segments once there are more than `segments`.
+        pub fn max_segments(mut self, segments: usize) -> Self {
+            self.max_segments = Some(segments.max(1));
+            self
+        }
+
+        /// Creates the segment following the current one, and removes old segments.
+        fn rotate(&mut self) -> io::Result<()> {
+            let segment = match self.current.take() {
+                Some((_, segment, _, _)) => segment + 1,
+                None => {
+                    fs::create_dir_all(&self.directory)?;
+                    segments(&self.directory, &self.prefix, self.worker)?.last().map(|(segment, _)| segment + 1).unwrap_or(0)
+                },
+            };
+            let path = self.directory.join(format!("{}.{}.{}.log", self.prefix, self.worker, segment));
+            let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+            SegmentHeader::new::<T, C>(self.worker, segment).write_to(&mut file)?;
+            self.current = Some((file, segment, 0, Instant::now()));
+
+            if let Some(max_segments) = self.max_segments {
+                let existing = segments(&self.directory, &self.prefix, self.worker)?;
+                let excess = existing.len().saturating_sub(max_segments);
+                for (_, path) in existing.into_iter().take(excess) {
+                    fs::remove_file(path)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<T: Abomonation, C: Abomonation> EventPusher<T, C> for RotatingWriter<T, C> {
+        fn push(&mut self, event: Event<T, C>) {
+            self.buffer.clear();
+            // TODO: `push` has no mechanism to report errors, so we `expect`.
+            unsafe { ::abomonation::encode(&event, &mut self.buffer).expect("Event abomonation failed"); }
+            let length = self.buffer.len() as u64;
+
+            let rotate = match &self.current {
+                None => true,
+                Some((_, _, written, created)) => {
+                    *written > 0 && (
+                        self.max_bytes.map(|max| written + length > max).unwrap_or(false) ||
+                        self.max_age.map(|max| created.elapsed() >= max).unwrap_or(false)
+                    )
+                },
+            };
+            if rotate {
+                self.rotate().expect("Log segment rotation failed");
+            }
+
+            let (file, _, written, _) = self.current.as_mut().unwrap();
+            file.write_all(&self.buffer).expect("Event write failed");
+            *written += length;
+        }
+    }
+
+    /// An `EventIterator<T, C>` reading rotated segment files in order.
+    ///
+    /// The reader moves to the next segment once it has read all of the current segment and a
+    /// later segment exists, and so it can follow segments as they are written. If segments are
+    /// removed before they are read, the reader continues from the oldest remaining segment.
+    pub struct RotatingReader<T, C> {
+        directory: PathBuf,
+        prefix: String,
+        worker: usize,
+        /// The segment being read, and its number.
+        current: Option<(File, u64)>,
+        /// The header of the segment being read.
+        header: Option<SegmentHeader>,
+        buffer: Vec<u8>,
+        consumed: usize,
+        phant: ::std::marker::PhantomData<(T, C)>,
+    }
+
+    impl<T, C> RotatingReader<T, C> {
+        /// Allocates a reader of the segments of `worker`'s events, named by `prefix` in `directory`.
+        ///
+        /// Returns an error if the directory cannot be read, or if its first segment does not
+        /// hold events of type `Event<T, C>`.
+        pub fn open<P: AsRef<Path>>(directory: P, prefix: &str, worker: usize) -> io::Result<Self> {
+            let mut reader = Self {
+                directory: directory.as_ref().to_owned(),
+                prefix: prefix.to_owned(),
+                worker,
+                current: None,
+                header: None,
+                buffer: Vec::new(),
+                consumed: 0,
+                phant: ::std::marker::PhantomData,
+            };
+            reader.advance()?;
+            Ok(reader)
+        }
+
+        /// The header of the segment being read, if any.
+        pub fn header(&self) -> Option<&SegmentHeader> { self.header.as_ref() }
+
+        /// Opens the segment following the current one, returning false if there is none yet.
+        fn advance(&mut self) -> io::Result<bool> {
+            let following = self.current.as_ref().map(|(_, segment)| *segment + 1).unwrap_or(0);
+            let next = segments(&self.directory, &self.prefix, self.worker)?.into_iter().find(|(segment, _)| *segment >= following);
+            if let Some((segment, path)) = next {
+                let mut file = File::open(path)?;
+                let header = match SegmentHeader::read_from(&mut file) {
+                    Ok(header) => header,
+                    // The writer may not have finished writing the header.
+                    Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+                    Err(error) => return Err(error),
+                };
+                let expected = std::any::type_name::<Event<T, C>>();
+                if header.event_type != expected {
+                    let message = format!("segment holds {}, rather than {}", header.event_type, expected);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
+                self.current = Some((file, segment));
+                self.header = Some(header);
+                self.buffer.clear();
+                self.consumed = 0;
+                Ok(true)
+            }
+            else {
+                Ok(false)
+            }
+        }
+
+        /// Appends unread bytes of the current segment to the buffer, returning their number.
+        fn fill(&mut self) -> usize {
+            if self.consumed > 0 {
+                self.buffer.drain(.. self.consumed);
+                self.consumed = 0;
+            }
+            match self.current.as_mut() {
+                Some((file, _)) => file.read_to_end(&mut self.buffer).expect("Log segment read failed"),
+                None => 0,
+            }
+        }
+    }
+
+    impl<T: Abomonation, C: Abomonation> EventIterator<T, C> for RotatingReader<T, C> {
+        fn next(&mut self) -> Option<&Event<T, C>> {
+            loop {
+                if self.current.is_none() && !self.advance().expect("Log segment open failed") {
+                    return None;
+                }
+                if unsafe { ::abomonation::decode::<Event<T, C>>(&mut self.buffer[self.consumed..]) }.is_some() {
+                    let valid = self.buffer.len();
+                    let (item, rest) = unsafe { ::abomonation::decode::<Event<T, C>>(&mut self.buffer[self.consumed..]) }.unwrap();
+                    self.consumed = valid - rest.len();
+                    return Some(item);
+                }
+                if self.fill() > 0 {
+                    continue;
+                }
+                // The writer completes a segment before creating the next, so once the next exists
+                // and the current segment has been read again, the current segment is complete.
+                let following = self.current.as_ref().map(|(_, segment)| *segment + 1).unwrap();
+                let later = segments(&self.directory, &self.prefix, self.worker)
+                    .expect("Log directory read failed")
+                    .iter()
+                    .any(|(segment, _)| *segment >= following);
+                if !later {
+                    return None;
+                }
+                if self.fill() > 0 {
+                    continue;
+                }
+                if !self.advance().expect("Log segment open failed") {
+                    return None;
+                }
+            }
+        }
+    }
+}
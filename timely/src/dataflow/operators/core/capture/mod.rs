@@ -6,7 +6,8 @@
 //!
 //! The `capture_into` method requires a `P: EventPusher<T, D>`, which is some type accepting
 //! `Event<T, D>` inputs. This module provides several examples, including the linked list
-//! `EventLink<T, D>`, and the binary `EventWriter<T, D, W>` wrapping any `W: Write`.
+//! `EventLink<T, D>`, the binary `EventWriter<T, D, W>` wrapping any `W: Write`, and the
+//! `RotatingWriter<T, D>` writing to a bounded sequence of files.
 //!
 //! Streams are captured at the worker granularity, and one can replay an arbitrary subset of
 //! the captured streams on any number of workers (fewer, more, or as many as were captured).
@@ -80,6 +81,7 @@ pub use self::event::{Event, EventPusher};
 pub use self::event::link::EventLink;
 pub use self::event::binary::EventReader;
 pub use self::event::binary::EventWriter;
+pub use self::event::rotating::{RotatingReader, RotatingWriter};
 
 pub mod capture;
 pub mod replay;
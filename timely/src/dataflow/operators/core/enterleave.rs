@@ -225,6 +225,7 @@ where
                 target: self.index,
                 seq_no: self.counter,
                 length: bundle.data.len(),
+                bytes: bundle.data.size_in_bytes(),
             };
             let recv_event = MessagesEvent {
                 is_send: false,
@@ -16,6 +16,8 @@ use crate::communication::Push;
 use crate::dataflow::{Scope, ScopeParent, StreamCore};
 use crate::dataflow::channels::pushers::{Tee, Counter};
 use crate::dataflow::channels::Message;
+use crate::record::InputRecorder;
+use crate::accounting::{Account, AccountKind, Usage};
 
 
 // TODO : This is an exogenous input, but it would be nice to wrap a Subgraph in something
@@ -114,6 +116,9 @@ impl<G: Scope> Input for G where <G as ScopeParent>::Timestamp: TotalOrder {
         address.push(index);
 
         handle.activate.push(self.activator_for(&address[..]));
+        if self.config().account_buffers {
+            handle.accounts.push(self.accounting().borrow_mut().account(&address[..], AccountKind::Input));
+        }
 
         let progress = Rc::new(RefCell::new(ChangeBatch::new()));
 
@@ -181,6 +186,8 @@ pub struct Handle<T: Timestamp, C: Container> {
     buffer1: C,
     buffer2: C,
     now_at: T,
+    recorder: Option<InputRecorder<T, C>>,
+    accounts: Vec<Account>,
 }
 
 impl<T: Timestamp, C: Container> Handle<T, C> {
@@ -219,6 +226,8 @@ impl<T: Timestamp, C: Container> Handle<T, C> {
             buffer1: Default::default(),
             buffer2: Default::default(),
             now_at: T::minimum(),
+            recorder: None,
+            accounts: Vec::new(),
         }
     }
 
@@ -274,9 +283,18 @@ impl<T: Timestamp, C: Container> Handle<T, C> {
         self.pushers.push(pusher);
     }
 
+    /// Records the interactions with this handle, from now on.
+    pub(crate) fn set_recorder(&mut self, recorder: InputRecorder<T, C>) {
+        // flush current contents, so that the recording starts at a batch boundary.
+        if !self.buffer1.is_empty() { self.flush(); }
+        recorder.advance(&self.now_at);
+        self.recorder = Some(recorder);
+    }
+
     // flushes our buffer at each of the destinations. there can be more than one; clone if needed.
     #[inline(never)]
     fn flush(&mut self) {
+        if let Some(recorder) = &self.recorder { recorder.send(&self.buffer1); }
         for index in 0 .. self.pushers.len() {
             if index < self.pushers.len() - 1 {
                 self.buffer2.clone_from(&self.buffer1);
@@ -289,6 +307,9 @@ impl<T: Timestamp, C: Container> Handle<T, C> {
             }
         }
         self.buffer1.clear();
+        for account in self.accounts.iter() {
+            account.set(Usage::default());
+        }
     }
 
     // closes the current epoch, flushing if needed, shutting if needed, and updating the frontier.
@@ -339,6 +360,7 @@ impl<T: Timestamp, C: Container> Handle<T, C> {
         if !buffer.is_empty() {
             // flush buffered elements to ensure local fifo.
             if !self.buffer1.is_empty() { self.flush(); }
+            if let Some(recorder) = &self.recorder { recorder.send(buffer); }
 
             // push buffer (or clone of buffer) at each destination.
             for index in 0 .. self.pushers.len() {
@@ -367,6 +389,7 @@ impl<T: Timestamp, C: Container> Handle<T, C> {
         if !self.now_at.eq(&next) {
             self.close_epoch();
             self.now_at = next;
+            if let Some(recorder) = &self.recorder { recorder.advance(&self.now_at); }
             for progress in self.progress.iter() {
                 progress.borrow_mut().update(self.now_at.clone(), 1);
             }
@@ -424,6 +447,12 @@ impl<T: Timestamp, C: PushContainer> Handle<T, C> {
         if self.buffer1.len() == self.buffer1.capacity() {
             self.flush();
         }
+        else if !self.accounts.is_empty() {
+            let usage = Usage::of(&self.buffer1);
+            for account in self.accounts.iter() {
+                account.set(usage);
+            }
+        }
     }
 }
 
@@ -436,5 +465,6 @@ impl<T: Timestamp, C: Container> Default for Handle<T, C> {
 impl<T:Timestamp, C: Container> Drop for Handle<T, C> {
     fn drop(&mut self) {
         self.close_epoch();
+        if let Some(recorder) = &self.recorder { recorder.close(); }
     }
 }
@@ -8,7 +8,8 @@ use std::default::Default;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::scheduling::{Schedule, Activations};
+use crate::scheduling::{Schedule, Activations, Budget};
+use crate::accounting::{Account, AccountKind};
 
 use crate::progress::{Source, Target};
 use crate::progress::{Timestamp, Operate, operate::SharedProgress, Antichain};
@@ -177,6 +178,23 @@ impl<G: Scope> OperatorBuilder<G> {
     pub fn operator_info(&self) -> OperatorInfo {
         OperatorInfo::new(self.index, self.global, &self.address[..])
     }
+
+    /// A budget for invocations of the operator logic, as configured for the worker.
+    pub fn budget(&self) -> Budget {
+        let config = self.scope.config();
+        let activator = self.scope.activator_for(&self.address[..]);
+        Budget::new(config.operator_time_slice, config.operator_fuel, activator)
+    }
+
+    /// Opens an account for records held by the operator's output buffers, if configured.
+    pub(crate) fn buffer_account(&self) -> Option<Account> {
+        if self.scope.config().account_buffers {
+            Some(self.scope.accounting().borrow_mut().account(&self.address[..], AccountKind::Buffer))
+        }
+        else {
+            None
+        }
+    }
 }
 
 struct OperatorCore<T, L>
@@ -22,6 +22,7 @@ use crate::dataflow::operators::generic::operator_info::OperatorInfo;
 use crate::dataflow::operators::generic::builder_raw::OperatorShape;
 
 use crate::logging::TimelyLogger as Logger;
+use crate::scheduling::Budget;
 
 use super::builder_raw::OperatorBuilder as OperatorBuilderRaw;
 
@@ -120,6 +121,9 @@ impl<G: Scope> OperatorBuilder<G> {
         self.internal.borrow_mut().push(internal.clone());
 
         let mut buffer = PushBuffer::new(PushCounter::new(tee));
+        if let Some(account) = self.builder.buffer_account() {
+            buffer.set_account(account);
+        }
         self.produced.push(buffer.inner().produced().clone());
 
         for (summary, connection) in self.summaries.iter().zip(connection.into_iter()) {
@@ -141,6 +145,56 @@ impl<G: Scope> OperatorBuilder<G> {
         })
     }
 
+    /// Creates an operator implementation from supplied logic constructor, with a budget.
+    ///
+    /// The supplied logic receives a [Budget] for each invocation, derived from the
+    /// worker configuration. Logic that may perform substantial work should check the
+    /// budget as it goes, and once it is exhausted should retain its remaining work,
+    /// call `yield_now()`, and return. The operator will then be rescheduled.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::VecDeque;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0 .. 100u64).to_stream(scope);
+    ///     let mut builder = OperatorBuilder::new("Budgeted".to_owned(), scope.clone());
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     builder.build_budgeted(|_capabilities| {
+    ///         let mut pending = VecDeque::new();
+    ///         move |_frontiers, budget| {
+    ///             input.for_each(|_time, data| pending.extend(data.iter().cloned()));
+    ///             while !budget.exhausted() {
+    ///                 match pending.pop_front() {
+    ///                     Some(_record) => budget.consume(1),
+    ///                     None => break,
+    ///                 }
+    ///             }
+    ///             if !pending.is_empty() {
+    ///                 budget.yield_now();
+    ///             }
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn build_budgeted<B, L>(self, constructor: B)
+    where
+        B: FnOnce(Vec<Capability<G::Timestamp>>) -> L,
+        L: FnMut(&[MutableAntichain<G::Timestamp>], &mut Budget)+'static
+    {
+        let mut budget = self.builder.budget();
+        self.build(|caps| {
+            let mut logic = constructor(caps);
+            move |frontier| {
+                budget.reset();
+                logic(frontier, &mut budget);
+            }
+        })
+    }
+
     /// Creates an operator implementation from supplied logic constructor.
     ///
     /// Unlike `build`, the supplied closure can indicate if the operator
@@ -52,6 +52,7 @@ pub use self::core::rc;
 pub mod result;
 
 pub mod aggregation;
+pub mod checkpoint;
 pub mod generic;
 
 pub use self::core::reclock;
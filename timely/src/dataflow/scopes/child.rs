@@ -14,6 +14,9 @@ use crate::order::Product;
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelyProgressLogger as ProgressLogger;
 use crate::worker::{AsWorker, Config};
+use crate::accounting::Accounting;
+use crate::utilization::Counters;
+use crate::graph::Graph;
 
 use super::{ScopeParent, Scope};
 
@@ -70,6 +73,15 @@ where
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.parent.log_register()
     }
+    fn accounting(&self) -> Rc<RefCell<Accounting>> {
+        self.parent.accounting()
+    }
+    fn utilization_counters(&self) -> Rc<RefCell<Counters>> {
+        self.parent.utilization_counters()
+    }
+    fn graph(&self) -> Rc<RefCell<Graph>> {
+        self.parent.graph()
+    }
 }
 
 impl<'a, G, T> Scheduler for Child<'a, G, T>
@@ -102,6 +114,7 @@ where
     }
 
     fn add_operator_with_indices(&mut self, operator: Box<dyn Operate<Self::Timestamp>>, local: usize, global: usize) {
+        self.graph().borrow_mut().add_operator(global, operator.path(), operator.name(), operator.inputs(), operator.outputs());
         self.subgraph.borrow_mut().add_child(operator, local, global);
     }
 
@@ -111,6 +124,14 @@ where
 
     #[inline]
     fn scoped<T2, R, F>(&mut self, name: &str, func: F) -> R
+    where
+        T2: Timestamp+Refines<T>,
+        F: FnOnce(&mut Child<Self, T2>) -> R,
+    {
+        self.try_scoped(name, func).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_scoped<T2, R, F>(&mut self, name: &str, func: F) -> Result<R, String>
     where
         T2: Timestamp+Refines<T>,
         F: FnOnce(&mut Child<Self, T2>) -> R,
@@ -128,11 +149,18 @@ where
             };
             func(&mut builder)
         };
-        let subscope = subscope.into_inner().build(self);
+        let subscope = match subscope.into_inner().try_build(self) {
+            Ok(subscope) => subscope,
+            Err(error) => {
+                // The index is allocated, and must be occupied for the scope to be built.
+                self.subgraph.borrow_mut().add_placeholder(index);
+                return Err(error);
+            },
+        };
 
         self.add_operator_with_index(Box::new(subscope), index);
 
-        result
+        Ok(result)
     }
 }
 
@@ -96,6 +96,41 @@ pub trait Scope: ScopeParent {
         T: Timestamp+Refines<<Self as ScopeParent>::Timestamp>,
         F: FnOnce(&mut Child<Self, T>) -> R;
 
+    /// Creates a dataflow subgraph as `scoped`, reporting an error if it cannot be built.
+    ///
+    /// Rather than panic if the subgraph contains a cycle along which timestamps do not strictly
+    /// advance, this method returns a description of the operators and channels in each such
+    /// cycle. The subgraph is replaced by an operator that does nothing in that case, to which
+    /// streams entering or leaving the subgraph are not connected. The dataflow containing it
+    /// should be abandoned, as streams leaving the subgraph will never be produced.
+    ///
+    /// The default implementation calls `scoped`, and so panics rather than report the error.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{Concat, ConnectLoop, Feedback, ToStream};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let result = scope.try_scoped::<u64,_,_>("Cyclic", |inner| {
+    ///             let (handle, cycle) = inner.feedback::<Vec<u64>>(Default::default());
+    ///             (0 .. 10).to_stream(inner)
+    ///                      .concat(&cycle)
+    ///                      .connect_loop(handle);
+    ///         });
+    ///         assert!(result.is_err());
+    ///     });
+    /// });
+    /// ```
+    fn try_scoped<T, R, F>(&mut self, name: &str, func: F) -> Result<R, String>
+    where
+        T: Timestamp+Refines<<Self as ScopeParent>::Timestamp>,
+        F: FnOnce(&mut Child<Self, T>) -> R,
+    {
+        Ok(self.scoped::<T,R,F>(name, func))
+    }
+
     /// Creates a iterative dataflow subgraph.
     ///
     /// This method is a specialization of `scoped` which uses the `Product` timestamp
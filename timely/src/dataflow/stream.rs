@@ -47,6 +47,7 @@ impl<S: Scope, C: Container> StreamCore<S, C> {
             target: (target.node, target.port),
         }));
 
+        self.scope.graph().borrow_mut().add_channel(identifier, &self.scope.addr(), (self.name.node, self.name.port), (target.node, target.port));
         self.scope.add_edge(self.name, target);
         self.ports.add_pusher(pusher);
     }
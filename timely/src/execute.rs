@@ -1,6 +1,6 @@
 //! Starts a timely dataflow execution from configuration information and per-worker logic.
 
-use crate::communication::{initialize_from, Allocator, allocator::AllocateBuilder, WorkerGuards};
+use crate::communication::{initialize_cancellable, Allocator, allocator::AllocateBuilder, WorkerGuards};
 use crate::dataflow::scopes::Child;
 use crate::worker::Worker;
 use crate::{CommunicationConfig, WorkerConfig};
@@ -56,6 +56,19 @@ impl Config {
         Config::from_matches(&matches)
     }
 
+    /// Constructs a new configuration from the file at `path`, with overrides from the environment.
+    ///
+    /// The file is read as described in [crate::config], and any `TIMELY_*` environment variables
+    /// listed there take precedence over the values in the file.
+    ///
+    /// This method is only available if the `config` feature is enabled.
+    #[cfg(feature = "config")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Config, String> {
+        let mut file = crate::config::ConfigFile::from_file(path)?;
+        file.apply_env()?;
+        file.into_config()
+    }
+
     /// Constructs a `Config` that uses one worker thread and the
     /// defaults for all other parameters.
     pub fn thread() -> Config {
@@ -155,9 +168,7 @@ where
     let alloc = crate::communication::allocator::thread::Thread::new();
     let mut worker = crate::worker::Worker::new(WorkerConfig::default(), alloc);
     let result = func(&mut worker);
-    while worker.has_dataflows() {
-        worker.step_or_park(None);
-    }
+    worker.run();
     result
 }
 
@@ -179,6 +190,12 @@ where
 /// capture the results and drop them only when the calling thread has no
 /// other work to perform.
 ///
+/// The computation can be stopped early using the handle returned by the
+/// `cancel_handle` method of `WorkerGuards`, which causes each worker to drop
+/// its dataflows and any it constructs afterwards (see `Worker::cancel`).
+/// Cancellation reaches workers in other processes through the workers of
+/// this process.
+///
 /// # Examples
 /// ```rust
 /// use timely::dataflow::operators::{ToStream, Inspect};
@@ -228,10 +245,12 @@ where
 
     if let CommunicationConfig::Cluster { ref mut log_fn, .. } = config.communication {
 
-        *log_fn = Box::new(|events_setup| {
+        let comm_log_addr = config.worker.communication_log_address.clone()
+            .or_else(|| ::std::env::var("TIMELY_COMM_LOG_ADDR").ok());
 
-            let mut result = None;
-            if let Ok(addr) = ::std::env::var("TIMELY_COMM_LOG_ADDR") {
+        // A configured address replaces any loggers supplied with the communication configuration.
+        if let Some(addr) = comm_log_addr {
+            *log_fn = Box::new(move |events_setup| {
 
                 use ::std::net::TcpStream;
                 use crate::logging::BatchLogger;
@@ -239,33 +258,35 @@ where
 
                 eprintln!("enabled COMM logging to {}", addr);
 
-                if let Ok(stream) = TcpStream::connect(&addr) {
+                if let Ok(stream) = TcpStream::connect(addr.as_str()) {
                     let writer = EventWriter::new(stream);
                     let mut logger = BatchLogger::new(writer);
-                    result = Some(crate::logging_core::Logger::new(
+                    Some(crate::logging_core::Logger::new(
                         ::std::time::Instant::now(),
                         ::std::time::Duration::default(),
                         events_setup,
                         move |time, data| logger.publish_batch(time, data)
-                    ));
+                    ))
                 }
                 else {
                     panic!("Could not connect to communication log address: {:?}", addr);
                 }
-            }
-            result
-        });
+            });
+        }
     }
 
     let (allocators, other) = config.communication.try_build()?;
 
     let worker_config = config.worker;
-    initialize_from(allocators, other, move |allocator| {
+    initialize_cancellable(allocators, other, move |allocator, cancel| {
 
         let mut worker = Worker::new(worker_config.clone(), allocator);
+        worker.set_cancel_handle(cancel);
 
-        // If an environment variable is set, use it as the default timely logging.
-        if let Ok(addr) = ::std::env::var("TIMELY_WORKER_LOG_ADDR") {
+        // If an address is configured, or an environment variable is set, use it as the default timely logging.
+        let timely_log_addr = worker_config.timely_log_address.clone()
+            .or_else(|| ::std::env::var("TIMELY_WORKER_LOG_ADDR").ok());
+        if let Some(addr) = timely_log_addr {
 
             use ::std::net::TcpStream;
             use crate::logging::{BatchLogger, TimelyEvent};
@@ -285,9 +306,7 @@ where
         }
 
         let result = func(&mut worker);
-        while worker.has_dataflows() {
-            worker.step_or_park(None);
-        }
+        worker.run();
         result
     })
 }
@@ -383,12 +402,11 @@ where
     A: AllocateBuilder+'static,
     T: Send+'static,
     F: Fn(&mut Worker<<A as AllocateBuilder>::Allocator>)->T+Send+Sync+'static {
-    initialize_from(builders, others, move |allocator| {
+    initialize_cancellable(builders, others, move |allocator, cancel| {
         let mut worker = Worker::new(worker_config.clone(), allocator);
+        worker.set_cancel_handle(cancel);
         let result = func(&mut worker);
-        while worker.has_dataflows() {
-            worker.step_or_park(None);
-        }
+        worker.run();
         result
     })
 }
@@ -0,0 +1,216 @@
+//! The structure of the dataflows installed in a worker.
+//!
+//! Each worker records the operators and channels of its dataflows as they are constructed,
+//! independently of logging. A snapshot is available from
+//! [Worker::dataflow_graph](crate::worker::Worker::dataflow_graph), and during construction from
+//! [AsWorker::graph](crate::worker::AsWorker::graph). The [Graph] can be rendered in the Graphviz
+//! DOT language, where each scope is drawn as a cluster, and as JSON.
+//!
+//! Operators are identified by their addresses, in which index zero within a scope refers to the
+//! scope's own inputs and outputs. Channels report the parallelization contract that connects
+//! them, `"Pipeline"` or `"Exchange"`, and no contract if they were connected directly, as are
+//! channels entering and leaving scopes. The operators and channels of a dataflow are discarded
+//! once the dataflow completes or is dropped.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+//!
+//! timely::execute_directly(|worker| {
+//!     worker.dataflow::<u64,_,_>(|scope| {
+//!         (0 .. 10).to_stream(scope)
+//!                  .exchange(|x| *x)
+//!                  .inspect(|x| println!("seen: {:?}", x));
+//!     });
+//!
+//!     let graph = worker.dataflow_graph();
+//!     assert!(graph.channels().values().any(|channel| channel.pact == Some("Exchange")));
+//!     println!("{}", graph.dot());
+//! });
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// An operator of a dataflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorNode {
+    /// Worker-unique identifier for the operator, as in `OperatesEvent`.
+    pub id: usize,
+    /// Sequence of nested scope identifiers indicating the path from the root to the operator.
+    pub addr: Vec<usize>,
+    /// The name of the operator.
+    pub name: String,
+    /// The number of inputs of the operator.
+    pub inputs: usize,
+    /// The number of outputs of the operator.
+    pub outputs: usize,
+}
+
+/// A channel between operators of a dataflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelEdge {
+    /// Worker-unique identifier for the channel, as in `ChannelsEvent`.
+    pub id: usize,
+    /// Sequence of nested scope identifiers indicating the path from the root to the scope.
+    pub scope_addr: Vec<usize>,
+    /// Source descriptor, indicating operator index and output port.
+    pub source: (usize, usize),
+    /// Target descriptor, indicating operator index and input port.
+    pub target: (usize, usize),
+    /// The parallelization contract of the channel, if it was connected by one.
+    pub pact: Option<&'static str>,
+}
+
+impl ChannelEdge {
+    /// The address of the source operator, or of the scope if the source index is zero.
+    pub fn source_addr(&self) -> Vec<usize> { endpoint(&self.scope_addr, self.source.0) }
+    /// The address of the target operator, or of the scope if the target index is zero.
+    pub fn target_addr(&self) -> Vec<usize> { endpoint(&self.scope_addr, self.target.0) }
+}
+
+/// The operators and channels of dataflows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    operators: BTreeMap<Vec<usize>, OperatorNode>,
+    channels: BTreeMap<usize, ChannelEdge>,
+}
+
+impl Graph {
+    /// Allocates an empty graph.
+    pub fn new() -> Self { Self::default() }
+
+    /// Records an operator.
+    pub(crate) fn add_operator(&mut self, id: usize, addr: &[usize], name: &str, inputs: usize, outputs: usize) {
+        let operator = OperatorNode { id, addr: addr.to_vec(), name: name.to_owned(), inputs, outputs };
+        self.operators.insert(operator.addr.clone(), operator);
+    }
+
+    /// Records a channel, whose contract may already be recorded.
+    pub(crate) fn add_channel(&mut self, id: usize, scope_addr: &[usize], source: (usize, usize), target: (usize, usize)) {
+        let channel = self.channels.entry(id).or_default();
+        channel.id = id;
+        channel.scope_addr = scope_addr.to_vec();
+        channel.source = source;
+        channel.target = target;
+    }
+
+    /// Records the parallelization contract of a channel.
+    pub(crate) fn set_pact(&mut self, id: usize, pact: &'static str) {
+        self.channels.entry(id).or_default().pact = Some(pact);
+    }
+
+    /// Discards the operators and channels of dataflows for which `installed` returns false.
+    pub(crate) fn retain_dataflows<F: FnMut(usize)->bool>(&mut self, mut installed: F) {
+        self.operators.retain(|addr, _| addr.first().map(|index| installed(*index)).unwrap_or(false));
+        self.channels.retain(|_, channel| channel.scope_addr.first().map(|index| installed(*index)).unwrap_or(false));
+    }
+
+    /// The operators, by address.
+    pub fn operators(&self) -> &BTreeMap<Vec<usize>, OperatorNode> { &self.operators }
+
+    /// The channels, by identifier.
+    pub fn channels(&self) -> &BTreeMap<usize, ChannelEdge> { &self.channels }
+
+    /// The operators and channels of the dataflow with index `dataflow`.
+    pub fn dataflow(&self, dataflow: usize) -> Graph {
+        let mut graph = self.clone();
+        graph.retain_dataflows(|index| index == dataflow);
+        graph
+    }
+
+    /// The graph in the Graphviz DOT language.
+    pub fn dot(&self) -> String {
+        let mut dot = String::from("digraph dataflow {\n    node [shape=box];\n");
+        self.dot_scope(&[], 1, &mut dot);
+        for channel in self.channels.values() {
+            let (label, style) = match channel.pact {
+                Some("Exchange") => ("Exchange", "bold"),
+                Some(pact) => (pact, "solid"),
+                None => ("", "dashed"),
+            };
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [label=\"{}\", style={}];",
+                node(&channel.source_addr()),
+                node(&channel.target_addr()),
+                escape(label),
+                style,
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the operators within the scope at `addr`.
+    fn dot_scope(&self, addr: &[usize], depth: usize, dot: &mut String) {
+        let indent = "    ".repeat(depth);
+        let children = self.operators.values().filter(|operator| {
+            operator.addr.len() == addr.len() + 1 && operator.addr.starts_with(addr)
+        });
+        for operator in children {
+            let label = format!("{}\\n{:?}", escape(&operator.name), operator.addr);
+            let scope = self.operators.keys().any(|other| other.len() > operator.addr.len() && other.starts_with(&operator.addr));
+            if scope {
+                let _ = writeln!(dot, "{}subgraph cluster_{} {{", indent, node(&operator.addr));
+                let _ = writeln!(dot, "{}    label=\"{}\";", indent, label);
+                let _ = writeln!(dot, "{}    {} [label=\"{}\", shape=ellipse];", indent, node(&operator.addr), escape(&operator.name));
+                self.dot_scope(&operator.addr, depth + 1, dot);
+                let _ = writeln!(dot, "{}}}", indent);
+            }
+            else {
+                let _ = writeln!(dot, "{}{} [label=\"{}\"];", indent, node(&operator.addr), label);
+            }
+        }
+    }
+
+    /// The graph as a JSON object, with lists of `"operators"` and `"channels"`.
+    ///
+    /// Channel endpoints are described by the address of their operator and by their port, and
+    /// channels without a contract have a `"pact"` of `null`.
+    pub fn json(&self) -> String {
+        let operators: Vec<String> = self.operators.values().map(|operator| format!(
+            r#"{{"id":{},"addr":{:?},"name":"{}","inputs":{},"outputs":{}}}"#,
+            operator.id, operator.addr, escape(&operator.name), operator.inputs, operator.outputs,
+        )).collect();
+        let channels: Vec<String> = self.channels.values().map(|channel| format!(
+            r#"{{"id":{},"scope":{:?},"source":{{"addr":{:?},"port":{}}},"target":{{"addr":{:?},"port":{}}},"pact":{}}}"#,
+            channel.id, channel.scope_addr,
+            channel.source_addr(), channel.source.1,
+            channel.target_addr(), channel.target.1,
+            channel.pact.map(|pact| format!("\"{}\"", escape(pact))).unwrap_or_else(|| "null".to_owned()),
+        )).collect();
+        format!("{{\"operators\":[{}],\"channels\":[{}]}}", operators.join(","), channels.join(","))
+    }
+}
+
+/// The address of the operator at `index` in the scope at `scope_addr`, where zero is the scope.
+fn endpoint(scope_addr: &[usize], index: usize) -> Vec<usize> {
+    let mut addr = scope_addr.to_vec();
+    if index > 0 { addr.push(index); }
+    addr
+}
+
+/// The DOT identifier of the node for the operator at `addr`.
+fn node(addr: &[usize]) -> String {
+    let mut node = String::from("op");
+    for index in addr {
+        let _ = write!(node, "_{}", index);
+    }
+    node
+}
+
+/// Escapes `text` for use in a DOT or JSON string.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            character if (character as u32) < 0x20 => { let _ = write!(result, "\\u{:04x}", character as u32); },
+            character => result.push(character),
+        }
+    }
+    result
+}
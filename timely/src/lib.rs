@@ -54,6 +54,11 @@
 //! input using `new_input`, and add a dataflow `inspect` operator to print each observed record.
 //! We then introduce input at increasing rounds, indicate the advance to the system (promising
 //! that we will introduce no more input at prior rounds), and step the computation.
+//!
+//! With the `tracing` feature, each worker reports its work to the [`tracing`](https://docs.rs/tracing)
+//! ecosystem: each call to schedule an operator is a `schedule` span, recording the operator's name,
+//! address, worker-unique identifier, and worker index, and each time a worker parks is a `park` span.
+//! Both are at the `TRACE` level.
 
 #![forbid(missing_docs)]
 
@@ -103,6 +108,13 @@ pub mod dataflow;
 pub mod synchronization;
 pub mod execute;
 pub mod order;
+pub mod rescale;
+pub mod record;
+pub mod simulation;
+pub mod config;
+pub mod accounting;
+pub mod utilization;
+pub mod graph;
 
 pub mod logging;
 // pub mod log_events;
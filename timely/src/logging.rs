@@ -1,5 +1,10 @@
 //! Traits, implementations, and macros related to logging timely events.
 
+pub mod analysis;
+pub mod chrome;
+pub mod hub;
+pub mod metrics;
+
 /// Type alias for logging timely events.
 pub type WorkerIdentifier = usize;
 /// Logger type for worker-local logging.
@@ -10,6 +15,7 @@ pub type TimelyLogger = Logger<TimelyEvent>;
 pub type TimelyProgressLogger = Logger<TimelyProgressEvent>;
 
 use std::time::Duration;
+use crate::accounting::AccountKind;
 use crate::dataflow::operators::capture::{Event, EventPusher};
 
 /// Logs events as a timely stream, with progress statements.
@@ -167,6 +173,8 @@ pub struct MessagesEvent {
     pub seq_no: usize,
     /// Number of typed records in the message.
     pub length: usize,
+    /// Estimated size of the records in bytes, if the container reports it.
+    pub bytes: Option<usize>,
 }
 
 /// Records the starting and stopping of an operator.
@@ -274,6 +282,37 @@ impl ParkEvent {
     pub fn unpark() -> Self { ParkEvent::Unpark }
 }
 
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The memory held by a component of an operator, reported when it changes.
+pub struct MemoryEvent {
+    /// Sequence of nested scope identifiers indicating the path from the root to the operator.
+    pub addr: Vec<usize>,
+    /// The kind of component holding the memory.
+    pub kind: AccountKind,
+    /// The number of records held.
+    pub records: usize,
+    /// The estimated number of bytes held.
+    pub bytes: usize,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The input frontiers of an operator, and how far they lag the inputs of its scope.
+///
+/// Reported when the frontiers of the operator change, and when the inputs of its scope advance
+/// while the operator lags them. The inputs of a scope are the frontier of the capabilities held
+/// by the scope's own inputs and by its operators without inputs, such as `input_from`.
+pub struct FrontierEvent {
+    /// Worker-unique identifier for the operator, linkable to the identifiers in `OperatesEvent`.
+    pub id: usize,
+    /// The frontier of each input of the operator, with timestamps formatted by `Debug`.
+    pub frontiers: Vec<Vec<String>>,
+    /// The frontier of the inputs of the scope, with timestamps formatted by `Debug`.
+    pub inputs: Vec<String>,
+    /// For each input of the operator, the time since the inputs of the scope advanced beyond
+    /// its frontier, or zero if the frontier has kept up with them.
+    pub lag: Vec<Duration>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Abomonation, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// An event in a timely worker
 pub enum TimelyEvent {
@@ -303,6 +342,10 @@ pub enum TimelyEvent {
     Park(ParkEvent),
     /// Unstructured event.
     Text(String),
+    /// Memory held by an operator.
+    Memory(MemoryEvent),
+    /// Operator input frontiers and their lag.
+    Frontier(FrontierEvent),
 }
 
 impl From<OperatesEvent> for TimelyEvent {
@@ -352,3 +395,11 @@ impl From<InputEvent> for TimelyEvent {
 impl From<ParkEvent> for TimelyEvent {
     fn from(v: ParkEvent) -> TimelyEvent { TimelyEvent::Park(v) }
 }
+
+impl From<MemoryEvent> for TimelyEvent {
+    fn from(v: MemoryEvent) -> TimelyEvent { TimelyEvent::Memory(v) }
+}
+
+impl From<FrontierEvent> for TimelyEvent {
+    fn from(v: FrontierEvent) -> TimelyEvent { TimelyEvent::Frontier(v) }
+}
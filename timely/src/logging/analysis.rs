@@ -0,0 +1,385 @@
+//! Offline analysis of captured "timely" log streams.
+//!
+//! Workers whose "timely" log stream is written by an `EventWriter`, for example to the address
+//! in `TIMELY_WORKER_LOG_ADDR`, produce a stream of `Event<Duration, Vec<(Duration, WorkerIdentifier, TimelyEvent)>>`.
+//! An [Analysis] reads any number of these streams, and summarizes
+//!
+//! * the dataflow graph, from `OperatesEvent` and `ChannelsEvent`,
+//! * the time each worker spent in each operator, and how unevenly it was spread across workers,
+//! * the messages, records, and bytes sent on each channel,
+//! * the time each worker spent in dataflows and parked.
+//!
+//! The summaries are available as data, as text tables from [Analysis::report], and as a Graphviz
+//! graph from [Analysis::dot]. The `logging-analyze` example applies them to log files.
+//!
+//! The workers are expected to construct the same dataflows, so that operators are identified
+//! across workers by their addresses, and channels by their identifiers. The time spent in a
+//! nested scope includes the time spent in the operators within it.
+//!
+//! # Examples
+//! ```no_run
+//! use std::fs::File;
+//! use timely::logging::analysis::Analysis;
+//!
+//! let mut analysis = Analysis::new();
+//! for path in std::env::args().skip(1) {
+//!     analysis.read(File::open(path).unwrap()).unwrap();
+//! }
+//! println!("{}", analysis.report());
+//! std::fs::write("dataflow.dot", analysis.dot()).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::{self, Read};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::dataflow::operators::capture::{Event, EventReader};
+use crate::dataflow::operators::capture::event::EventIterator;
+use super::{StartStop, TimelyEvent, ParkEvent, WorkerIdentifier};
+
+/// The time spent in one operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorSummary {
+    /// Sequence of nested scope identifiers indicating the path from the root to the operator.
+    pub addr: Vec<usize>,
+    /// The name of the operator.
+    pub name: String,
+    /// The number of times workers scheduled the operator.
+    pub activations: u64,
+    /// The time spent in the operator, by worker.
+    pub busy: BTreeMap<WorkerIdentifier, Duration>,
+}
+
+impl OperatorSummary {
+    /// The time spent in the operator by all workers.
+    pub fn total(&self) -> Duration {
+        self.busy.values().sum()
+    }
+
+    /// The ratio of the largest time any worker spent in the operator to the mean time.
+    ///
+    /// A ratio of one indicates the work was spread evenly. Returns `None` if no time was spent.
+    pub fn skew(&self) -> Option<f64> {
+        skew(self.busy.values().cloned())
+    }
+}
+
+/// The data sent on one channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelSummary {
+    /// Worker-unique identifier for the channel.
+    pub id: usize,
+    /// Sequence of nested scope identifiers indicating the path from the root to the scope.
+    pub scope_addr: Vec<usize>,
+    /// Source descriptor, indicating operator index and output port.
+    pub source: (usize, usize),
+    /// Target descriptor, indicating operator index and input port.
+    pub target: (usize, usize),
+    /// The number of messages sent by all workers.
+    pub messages: u64,
+    /// The number of records sent by all workers.
+    pub records: u64,
+    /// The estimated number of bytes sent by all workers, for containers that report it.
+    pub bytes: u64,
+}
+
+/// The time spent by one worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerSummary {
+    /// The time between the first and last events of the worker.
+    pub elapsed: Duration,
+    /// The time the worker spent in dataflows.
+    pub busy: Duration,
+    /// The time the worker spent parked.
+    pub parked: Duration,
+    /// The number of times the worker scheduled operators, including dataflows.
+    pub activations: u64,
+}
+
+/// Summaries of captured "timely" log streams.
+#[derive(Default)]
+pub struct Analysis {
+    operators: BTreeMap<Vec<usize>, OperatorSummary>,
+    channels: BTreeMap<usize, ChannelSummary>,
+    workers: BTreeMap<WorkerIdentifier, WorkerSummary>,
+    /// Operator addresses, by worker and operator identifier.
+    addresses: HashMap<(WorkerIdentifier, usize), Vec<usize>>,
+    /// Start times of operators being scheduled, by worker and operator identifier.
+    scheduled: HashMap<(WorkerIdentifier, usize), Duration>,
+    /// Start times of parked workers.
+    parked: HashMap<WorkerIdentifier, Duration>,
+    /// Times of the first and last events of each worker.
+    spans: HashMap<WorkerIdentifier, (Duration, Duration)>,
+}
+
+impl Analysis {
+    /// Creates an analysis of no events.
+    pub fn new() -> Self { Self::default() }
+
+    /// Reads a log stream written by an `EventWriter`, until it is exhausted.
+    pub fn read<R: Read>(&mut self, reader: R) -> io::Result<()> {
+        let status = Rc::new(RefCell::new(ReadStatus::Reading));
+        let mut events = EventReader::<Duration, Vec<(Duration, WorkerIdentifier, TimelyEvent)>, _>::new(
+            TrackedReader { reader, status: status.clone() }
+        );
+        loop {
+            match events.next() {
+                Some(Event::Messages(_, data)) => {
+                    for (time, worker, event) in data.iter() {
+                        self.observe(*time, *worker, event);
+                    }
+                },
+                Some(Event::Progress(_)) => { },
+                None => match std::mem::replace(&mut *status.borrow_mut(), ReadStatus::Reading) {
+                    ReadStatus::Reading => { },
+                    ReadStatus::Exhausted => return Ok(()),
+                    ReadStatus::Failed(error) => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Incorporates one logged event.
+    pub fn observe(&mut self, time: Duration, worker: WorkerIdentifier, event: &TimelyEvent) {
+        let span = self.spans.entry(worker).or_insert((time, time));
+        span.0 = std::cmp::min(span.0, time);
+        span.1 = std::cmp::max(span.1, time);
+        let summary = self.workers.entry(worker).or_default();
+        summary.elapsed = span.1 - span.0;
+
+        match event {
+            TimelyEvent::Operates(event) => {
+                self.addresses.insert((worker, event.id), event.addr.clone());
+                let operator = self.operators.entry(event.addr.clone()).or_default();
+                operator.addr.clone_from(&event.addr);
+                operator.name.clone_from(&event.name);
+            },
+            TimelyEvent::Channels(event) => {
+                let channel = self.channels.entry(event.id).or_default();
+                channel.id = event.id;
+                channel.scope_addr.clone_from(&event.scope_addr);
+                channel.source = event.source;
+                channel.target = event.target;
+            },
+            TimelyEvent::Schedule(event) => match event.start_stop {
+                StartStop::Start => { self.scheduled.insert((worker, event.id), time); },
+                StartStop::Stop => {
+                    let start = self.scheduled.remove(&(worker, event.id));
+                    let addr = self.addresses.get(&(worker, event.id));
+                    if let (Some(start), Some(addr)) = (start, addr) {
+                        let elapsed = time.saturating_sub(start);
+                        summary.activations += 1;
+                        if addr.len() == 1 {
+                            summary.busy += elapsed;
+                        }
+                        if let Some(operator) = self.operators.get_mut(addr) {
+                            operator.activations += 1;
+                            *operator.busy.entry(worker).or_default() += elapsed;
+                        }
+                    }
+                },
+            },
+            TimelyEvent::Park(ParkEvent::Park(_)) => {
+                self.parked.insert(worker, time);
+            },
+            TimelyEvent::Park(ParkEvent::Unpark) => {
+                if let Some(start) = self.parked.remove(&worker) {
+                    summary.parked += time.saturating_sub(start);
+                }
+            },
+            TimelyEvent::Messages(event) if event.is_send => {
+                let channel = self.channels.entry(event.channel).or_default();
+                channel.id = event.channel;
+                channel.messages += 1;
+                channel.records += event.length as u64;
+                channel.bytes += event.bytes.unwrap_or(0) as u64;
+            },
+            _ => { },
+        }
+    }
+
+    /// The time spent in each operator, by operator address.
+    pub fn operators(&self) -> &BTreeMap<Vec<usize>, OperatorSummary> { &self.operators }
+
+    /// The data sent on each channel, by channel identifier.
+    pub fn channels(&self) -> &BTreeMap<usize, ChannelSummary> { &self.channels }
+
+    /// The time spent by each worker.
+    pub fn workers(&self) -> &BTreeMap<WorkerIdentifier, WorkerSummary> { &self.workers }
+
+    /// The ratio of the largest time any worker spent in dataflows to the mean time.
+    pub fn worker_skew(&self) -> Option<f64> {
+        skew(self.workers.values().map(|worker| worker.busy))
+    }
+
+    /// Text tables of workers, operators by decreasing busy time, and channels.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+
+        let rows = self.workers.iter().map(|(index, worker)| vec![
+            index.to_string(),
+            format!("{:.3?}", worker.elapsed),
+            format!("{:.3?}", worker.busy),
+            format!("{:.3?}", worker.parked),
+            worker.activations.to_string(),
+        ]).collect();
+        report.push_str(&table(&["worker", "elapsed", "busy", "parked", "activations"], rows));
+        let _ = writeln!(report, "worker skew: {}\n", ratio(self.worker_skew()));
+
+        let mut operators: Vec<&OperatorSummary> = self.operators.values().collect();
+        operators.sort_by(|x, y| y.total().cmp(&x.total()).then_with(|| x.addr.cmp(&y.addr)));
+        let rows = operators.iter().map(|operator| vec![
+            format!("{:?}", operator.addr),
+            operator.name.clone(),
+            operator.activations.to_string(),
+            format!("{:.3?}", operator.total()),
+            format!("{:.3?}", operator.busy.values().max().cloned().unwrap_or_default()),
+            ratio(operator.skew()),
+        ]).collect();
+        report.push_str(&table(&["address", "operator", "activations", "busy", "max busy", "skew"], rows));
+        report.push('\n');
+
+        let rows = self.channels.values().map(|channel| vec![
+            channel.id.to_string(),
+            self.name(&endpoint(&channel.scope_addr, channel.source.0)),
+            self.name(&endpoint(&channel.scope_addr, channel.target.0)),
+            channel.messages.to_string(),
+            channel.records.to_string(),
+            channel.bytes.to_string(),
+        ]).collect();
+        report.push_str(&table(&["channel", "source", "target", "messages", "records", "bytes"], rows));
+
+        report
+    }
+
+    /// The dataflow graph in the Graphviz DOT language.
+    ///
+    /// Each scope is drawn as a cluster, containing a node for its own inputs and outputs, and
+    /// each channel as an edge labeled with the records sent on it.
+    pub fn dot(&self) -> String {
+        let mut dot = String::from("digraph dataflow {\n    node [shape=box];\n");
+        self.dot_scope(&[], 1, &mut dot);
+        for channel in self.channels.values() {
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [label=\"{} records\"];",
+                node(&endpoint(&channel.scope_addr, channel.source.0)),
+                node(&endpoint(&channel.scope_addr, channel.target.0)),
+                channel.records,
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the operators within the scope at `addr`.
+    fn dot_scope(&self, addr: &[usize], depth: usize, dot: &mut String) {
+        let indent = "    ".repeat(depth);
+        let children = self.operators.values().filter(|operator| {
+            operator.addr.len() == addr.len() + 1 && operator.addr.starts_with(addr)
+        });
+        for operator in children {
+            let label = format!("{}\\n{:?}\\n{:.3?}", escape(&operator.name), operator.addr, operator.total());
+            let scope = self.operators.keys().any(|other| other.len() > operator.addr.len() && other.starts_with(&operator.addr));
+            if scope {
+                let _ = writeln!(dot, "{}subgraph cluster_{} {{", indent, node(&operator.addr));
+                let _ = writeln!(dot, "{}    label=\"{}\";", indent, label);
+                let _ = writeln!(dot, "{}    {} [label=\"{}\", shape=ellipse];", indent, node(&operator.addr), escape(&operator.name));
+                self.dot_scope(&operator.addr, depth + 1, dot);
+                let _ = writeln!(dot, "{}}}", indent);
+            }
+            else {
+                let _ = writeln!(dot, "{}{} [label=\"{}\"];", indent, node(&operator.addr), label);
+            }
+        }
+    }
+
+    /// The name of the operator at `addr`, or its address if it is unknown.
+    fn name(&self, addr: &[usize]) -> String {
+        self.operators.get(addr).map(|operator| operator.name.clone()).unwrap_or_else(|| format!("{:?}", addr))
+    }
+}
+
+/// The state of a [TrackedReader].
+enum ReadStatus {
+    Reading,
+    Exhausted,
+    Failed(io::Error),
+}
+
+/// A reader that reports when it is exhausted or fails, which `EventReader` does not.
+struct TrackedReader<R: Read> {
+    reader: R,
+    status: Rc<RefCell<ReadStatus>>,
+}
+
+impl<R: Read> Read for TrackedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = self.reader.read(buf);
+        match &result {
+            Ok(0) => { *self.status.borrow_mut() = ReadStatus::Exhausted; },
+            Ok(_) => { },
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => { },
+            Err(error) => { *self.status.borrow_mut() = ReadStatus::Failed(io::Error::new(error.kind(), error.to_string())); },
+        }
+        result
+    }
+}
+
+/// The address of the operator at `index` in the scope at `scope_addr`, where zero is the scope.
+fn endpoint(scope_addr: &[usize], index: usize) -> Vec<usize> {
+    let mut addr = scope_addr.to_vec();
+    if index > 0 { addr.push(index); }
+    addr
+}
+
+/// The DOT identifier of the node for the operator at `addr`.
+fn node(addr: &[usize]) -> String {
+    let mut node = String::from("op");
+    for index in addr {
+        let _ = write!(node, "_{}", index);
+    }
+    node
+}
+
+/// Escapes `text` for use in a DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The ratio of the largest of `times` to their mean.
+fn skew<I: Iterator<Item=Duration>>(times: I) -> Option<f64> {
+    let times: Vec<f64> = times.map(|time| time.as_secs_f64()).collect();
+    let total: f64 = times.iter().sum();
+    if total > 0.0 {
+        let max = times.iter().cloned().fold(0.0, f64::max);
+        Some(max / (total / times.len() as f64))
+    }
+    else {
+        None
+    }
+}
+
+fn ratio(ratio: Option<f64>) -> String {
+    ratio.map(|ratio| format!("{:.2}", ratio)).unwrap_or_else(|| "-".to_owned())
+}
+
+/// Formats `rows` as a table with aligned columns.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = std::cmp::max(*width, cell.chars().count());
+        }
+    }
+    let mut text = String::new();
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(headers).chain(rows) {
+        let cells: Vec<String> = row.iter().zip(widths.iter()).map(|(cell, width)| format!("{:<1$}", cell, *width)).collect();
+        let _ = writeln!(text, "{}", cells.join("  ").trim_end());
+    }
+    text
+}
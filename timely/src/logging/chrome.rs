@@ -0,0 +1,187 @@
+//! Export of the "timely" log stream in the Chrome Trace Event format.
+//!
+//! A [TraceWriter] wraps a destination for a trace, and may be shared by the workers of a process.
+//! Each worker registers a [ChromeTrace] as its "timely" logger, which translates its events into
+//! trace events written to the shared destination. The resulting file can be opened in Perfetto
+//! (<https://ui.perfetto.dev>) or in `chrome://tracing`.
+//!
+//! The trace has one track for each worker. Each scheduling of an operator appears as a slice
+//! named after the operator, and each period the worker parks appears as a slice named "park".
+//! Messages between operators appear as flow arrows, from the slice in which they were sent to
+//! the slice in which they were received.
+//!
+//! The trace is completed once all clones of its writer are dropped.
+//!
+//! # Examples
+//! ```no_run
+//! use std::fs::File;
+//! use timely::logging::TimelyEvent;
+//! use timely::logging::chrome::{ChromeTrace, TraceWriter};
+//!
+//! let trace = TraceWriter::new(File::create("trace.json").unwrap());
+//! timely::execute_from_args(std::env::args(), move |worker| {
+//!     let mut sink = ChromeTrace::new(trace.clone());
+//!     worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+//!         sink.publish(data).expect("failed to write trace");
+//!     });
+//!     // construct and run dataflows ...
+//! }).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{StartStop, TimelyEvent, ParkEvent, WorkerIdentifier};
+
+/// A destination for a trace, shared by the workers that write to it.
+pub struct TraceWriter<W: Write> {
+    inner: Arc<Mutex<TraceInner<W>>>,
+}
+
+struct TraceInner<W: Write> {
+    writer: W,
+    /// Set once the first event has been written.
+    started: bool,
+}
+
+impl<W: Write> TraceInner<W> {
+    /// Writes events, each formatted as a JSON object, to the trace.
+    fn write(&mut self, events: &[String]) -> io::Result<()> {
+        for event in events {
+            let separator = if self.started { ",\n" } else { "[\n" };
+            self.started = true;
+            self.writer.write_all(separator.as_bytes())?;
+            self.writer.write_all(event.as_bytes())?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for TraceInner<W> {
+    fn drop(&mut self) {
+        let end = if self.started { "\n]\n" } else { "[]\n" };
+        // Errors cannot be reported from `drop`; the trace is simply left unterminated.
+        let _ = self.writer.write_all(end.as_bytes()).and_then(|()| self.writer.flush());
+    }
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Starts a trace written to `writer`.
+    pub fn new(writer: W) -> Self {
+        TraceWriter {
+            inner: Arc::new(Mutex::new(TraceInner { writer, started: false })),
+        }
+    }
+}
+
+impl<W: Write> Clone for TraceWriter<W> {
+    fn clone(&self) -> Self {
+        TraceWriter { inner: self.inner.clone() }
+    }
+}
+
+/// Translates the "timely" log stream of one worker into trace events.
+pub struct ChromeTrace<W: Write> {
+    writer: TraceWriter<W>,
+    /// Operator names and addresses, by operator identifier.
+    operators: HashMap<usize, (String, Vec<usize>)>,
+    /// Announced worker tracks.
+    workers: Vec<WorkerIdentifier>,
+    /// Formatted events not yet written.
+    pending: Vec<String>,
+}
+
+impl<W: Write> ChromeTrace<W> {
+    /// Creates a sink writing to the trace of `writer`.
+    pub fn new(writer: TraceWriter<W>) -> Self {
+        ChromeTrace {
+            writer,
+            operators: HashMap::new(),
+            workers: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Writes trace events for a batch of logged events, draining the batch.
+    ///
+    /// This method has the signature of the actions accepted by the logging registry, apart from
+    /// the time, and is meant to be called from within such an action.
+    pub fn publish(&mut self, data: &mut Vec<(Duration, WorkerIdentifier, TimelyEvent)>) -> io::Result<()> {
+        for (time, worker, event) in data.drain(..) {
+            self.translate(time, worker, event);
+        }
+        if !self.pending.is_empty() {
+            let mut inner = self.writer.inner.lock().map_err(|_| io::Error::new(io::ErrorKind::Other, "trace writer poisoned"))?;
+            inner.write(&self.pending[..])?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    fn translate(&mut self, time: Duration, worker: WorkerIdentifier, event: TimelyEvent) {
+        if !self.workers.contains(&worker) {
+            self.workers.push(worker);
+            self.pending.push(format!(
+                r#"{{"name":"thread_name","ph":"M","pid":0,"tid":{},"args":{{"name":"worker {}"}}}}"#,
+                worker, worker,
+            ));
+        }
+        let ts = micros(time);
+        match event {
+            TimelyEvent::Operates(event) => {
+                self.operators.insert(event.id, (event.name, event.addr));
+            },
+            TimelyEvent::Schedule(event) => {
+                let phase = match event.start_stop { StartStop::Start => "B", StartStop::Stop => "E" };
+                let (name, addr) = self.operators.get(&event.id).map(|(name, addr)| (name.as_str(), &addr[..])).unwrap_or(("unknown", &[]));
+                self.pending.push(format!(
+                    r#"{{"name":{},"cat":"operator","ph":"{}","pid":0,"tid":{},"ts":{},"args":{{"id":{},"addr":"{:?}"}}}}"#,
+                    string(name), phase, worker, ts, event.id, addr,
+                ));
+            },
+            TimelyEvent::Park(event) => {
+                let phase = match event { ParkEvent::Park(_) => "B", ParkEvent::Unpark => "E" };
+                self.pending.push(format!(
+                    r#"{{"name":"park","cat":"park","ph":"{}","pid":0,"tid":{},"ts":{}}}"#,
+                    phase, worker, ts,
+                ));
+            },
+            TimelyEvent::Messages(event) => {
+                // Messages from a worker to itself are recorded on both ends by the same worker.
+                let phase = if event.is_send { r#""ph":"s""# } else { r#""ph":"f","bp":"e""# };
+                self.pending.push(format!(
+                    r#"{{"name":"message","cat":"message",{},"id":"{}-{}-{}-{}","pid":0,"tid":{},"ts":{},"args":{{"channel":{},"length":{}}}}}"#,
+                    phase, event.channel, event.source, event.target, event.seq_no, worker, ts, event.channel, event.length,
+                ));
+            },
+            _ => { },
+        }
+    }
+}
+
+/// Formats a duration as fractional microseconds, the unit of trace timestamps.
+fn micros(time: Duration) -> String {
+    format!("{}.{:03}", time.as_micros(), time.subsec_nanos() % 1_000)
+}
+
+/// Formats `text` as a JSON string.
+fn string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            character if (character as u32) < 0x20 => { let _ = write!(result, "\\u{:04x}", character as u32); },
+            character => result.push(character),
+        }
+    }
+    result.push('"');
+    result
+}
@@ -0,0 +1,173 @@
+//! A process-wide destination for the log streams of several workers.
+//!
+//! Each logger is bound to a single thread, and so each worker would otherwise need its own log
+//! destination. A [LogHub] instead owns one `EventPusher`, for example an `EventWriter` wrapping a
+//! file or a connection, and a thread that writes to it. Each worker registers a [HubSink] as its
+//! logger, which sends batches of events to the hub through a lock-free queue.
+//!
+//! The hub writes the batches of all sources as a single stream, in the format of `BatchLogger`,
+//! which can be replayed or read as the stream of a single worker would be. Events keep their
+//! identifiers, which for timely logs are the worker indices, and the events of each source are
+//! written in the order they were logged, with timestamps that never decrease. The frontier of the
+//! combined stream is the least frontier of the sources, and the stream completes once each of
+//! the sources the hub was created with has dropped its sink.
+//!
+//! # Examples
+//! ```no_run
+//! use std::net::TcpStream;
+//! use timely::dataflow::operators::capture::EventWriter;
+//! use timely::logging::TimelyEvent;
+//! use timely::logging::hub::LogHub;
+//!
+//! let config = timely::Config::process(4);
+//! let stream = TcpStream::connect("127.0.0.1:8000").unwrap();
+//! let hub = LogHub::new(0 .. 4, EventWriter::new(stream));
+//! timely::execute(config, move |worker| {
+//!     let mut sink = hub.sink(worker.index());
+//!     worker.log_register().insert::<TimelyEvent,_>("timely", move |time, data| {
+//!         sink.publish_batch(time, data);
+//!     });
+//!     // construct and run dataflows ...
+//! }).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::dataflow::operators::capture::{Event, EventPusher};
+
+/// A message from a sink to its hub.
+enum Message<E, T> {
+    /// Events of a source, and the source's new frontier.
+    Batch { source: usize, time: Duration, data: Vec<(Duration, E, T)> },
+    /// The source will send no further events.
+    Close(usize),
+}
+
+/// A shared destination for the log streams of several sources.
+///
+/// The hub may be cloned, and writes until each of its sources has closed, or until all clones
+/// of the hub and all of its sinks are dropped. Dropping the last clone of the hub waits for the
+/// hub to finish writing.
+pub struct LogHub<E, T> {
+    inner: Arc<HubInner<E, T>>,
+}
+
+impl<E, T> Clone for LogHub<E, T> {
+    fn clone(&self) -> Self {
+        LogHub { inner: self.inner.clone() }
+    }
+}
+
+struct HubInner<E, T> {
+    sender: Option<Sender<Message<E, T>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<E, T> Drop for HubInner<E, T> {
+    fn drop(&mut self) {
+        // The hub stops once no senders remain, and so ours must be dropped first.
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<E: Send + 'static, T: Send + 'static> LogHub<E, T> {
+    /// Allocates a hub for the log streams of `sources`, which writes to `pusher`.
+    ///
+    /// Sources other than those in `sources` may also send events to the hub, but the stream of
+    /// the hub may complete before they have closed.
+    pub fn new<I, P>(sources: I, pusher: P) -> Self
+    where
+        I: IntoIterator<Item=usize>,
+        P: EventPusher<Duration, Vec<(Duration, E, T)>> + Send + 'static,
+    {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let sources = sources.into_iter().collect::<Vec<_>>();
+        let thread = std::thread::spawn(move || run(receiver, sources, pusher));
+        let inner = HubInner { sender: Some(sender), thread: Some(thread) };
+        LogHub { inner: Arc::new(inner) }
+    }
+
+    /// A sink for the log stream of `source`.
+    pub fn sink(&self, source: usize) -> HubSink<E, T> {
+        HubSink {
+            source,
+            time: Duration::default(),
+            sender: self.inner.sender.as_ref().expect("log hub closed").clone(),
+        }
+    }
+}
+
+/// Sends the log stream of one source to a [LogHub].
+///
+/// Dropping the sink closes the stream of its source.
+pub struct HubSink<E, T> {
+    source: usize,
+    /// The frontier of the source, as last reported.
+    time: Duration,
+    sender: Sender<Message<E, T>>,
+}
+
+impl<E, T> HubSink<E, T> {
+    /// Sends a batch of logged events and advances the frontier of the source, as `BatchLogger`.
+    pub fn publish_batch(&mut self, &time: &Duration, data: &mut Vec<(Duration, E, T)>) {
+        if !data.is_empty() || self.time < time {
+            self.time = std::cmp::max(self.time, time);
+            // An error indicates that the hub has stopped, and will write no further events.
+            let _ = self.sender.send(Message::Batch { source: self.source, time: self.time, data: data.drain(..).collect() });
+        }
+    }
+}
+
+impl<E, T> Drop for HubSink<E, T> {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Close(self.source));
+    }
+}
+
+/// Writes the batches received from sinks to `pusher`, until the stream completes.
+fn run<E, T, P>(receiver: Receiver<Message<E, T>>, sources: Vec<usize>, mut pusher: P)
+where
+    P: EventPusher<Duration, Vec<(Duration, E, T)>>,
+{
+    // The frontier of each open source, and the time of its latest event.
+    let mut open = sources.into_iter().map(|source| (source, (Duration::default(), Duration::default()))).collect::<HashMap<_,_>>();
+    // The frontier of the combined stream, initially held at the minimum time.
+    let mut frontier = Duration::default();
+
+    for message in receiver.iter() {
+        match message {
+            Message::Batch { source, time, mut data } => {
+                let (capability, latest) = open.entry(source).or_insert((frontier, frontier));
+                // Timestamps of a source never decrease, even if its loggers have different offsets.
+                for (event_time, _, _) in data.iter_mut() {
+                    if *event_time < *latest { *event_time = *latest; }
+                    *latest = *event_time;
+                }
+                if !data.is_empty() {
+                    pusher.push(Event::Messages(std::cmp::max(*capability, frontier), data));
+                }
+                *capability = std::cmp::max(*capability, time);
+            },
+            Message::Close(source) => { open.remove(&source); },
+        }
+
+        match open.values().map(|(capability, _)| *capability).min() {
+            Some(next) if next > frontier => {
+                pusher.push(Event::Progress(vec![(next, 1), (frontier, -1)]));
+                frontier = next;
+            },
+            Some(_) => { },
+            None => break,
+        }
+    }
+
+    pusher.push(Event::Progress(vec![(frontier, -1)]));
+}
@@ -0,0 +1,328 @@
+//! Metrics derived from the "timely" and communication log streams.
+//!
+//! A [Metrics] registry holds counters and histograms, and may be shared by the workers and the
+//! communication threads of a process. Each worker registers a [TimelyMetrics] sink as its "timely"
+//! logger, and each communication thread may use a [CommunicationMetrics] sink as its logger. The
+//! registry maintains
+//!
+//! * `timely_operator_schedule_seconds`, a histogram of the duration of operator schedulings,
+//! * `timely_channel_messages_total` and `timely_channel_records_total`, the messages and records
+//!   sent and received on each channel,
+//! * `timely_park_seconds_total` and `timely_parks_total`, the time workers spend parked,
+//! * `timely_communication_bytes_total` and `timely_communication_messages_total`, the bytes and
+//!   messages exchanged with each peer process.
+//!
+//! The registry renders its metrics in the Prometheus text exposition format, either on demand
+//! with [Metrics::render] or in response to scrapes of a listener passed to [Metrics::serve].
+//!
+//! # Examples
+//! ```no_run
+//! use std::net::TcpListener;
+//! use timely::logging::TimelyEvent;
+//! use timely::logging::metrics::Metrics;
+//!
+//! let metrics = Metrics::new();
+//! metrics.serve(TcpListener::bind("127.0.0.1:9100").unwrap());
+//! timely::execute_from_args(std::env::args(), move |worker| {
+//!     let mut sink = metrics.timely();
+//!     worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+//!         sink.publish(data);
+//!     });
+//!     // construct and run dataflows ...
+//! }).unwrap();
+//! ```
+//!
+//! Communication threads exist only for `CommunicationConfig::Cluster`, whose `log_fn` creates
+//! their loggers:
+//! ```
+//! use std::time::{Duration, Instant};
+//! use timely::communication::logging::CommunicationSetup;
+//! use timely::logging::metrics::Metrics;
+//! use timely::logging_core::Logger;
+//!
+//! let metrics = Metrics::new();
+//! let log_fn = Box::new(move |setup: CommunicationSetup| {
+//!     let mut sink = metrics.communication();
+//!     Some(Logger::new(Instant::now(), Duration::default(), setup, move |_time, data| {
+//!         sink.publish(data);
+//!     }))
+//! });
+//! # let _ = timely::CommunicationConfig::Cluster {
+//! #     threads: 1, process: 0, addresses: vec!["localhost:2101".to_owned()], report: false, log_fn,
+//! # };
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use timely_communication::logging::{CommunicationEvent, CommunicationSetup};
+
+use super::{StartStop, TimelyEvent, ParkEvent, WorkerIdentifier};
+
+/// The time a scraper has to send its request, or to receive the response.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+/// The largest request accepted from a scraper, in bytes.
+const MAX_REQUEST: usize = 8192;
+
+/// Upper bounds, in seconds, of the buckets of schedule duration histograms.
+const BUCKETS: [f64; 8] = [0.000_001, 0.000_01, 0.000_1, 0.001, 0.01, 0.1, 1.0, 10.0];
+
+/// A histogram of durations.
+#[derive(Default)]
+struct Histogram {
+    /// Observations in each bucket, with a final bucket for those exceeding all bounds.
+    buckets: [u64; BUCKETS.len() + 1],
+    sum: Duration,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = BUCKETS.iter().position(|bound| seconds <= *bound).unwrap_or(BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.sum += elapsed;
+        self.count += 1;
+    }
+}
+
+/// Counts of messages and of the records or bytes they contain.
+#[derive(Default)]
+struct Volume {
+    messages: u64,
+    amount: u64,
+}
+
+#[derive(Default)]
+struct MetricsInner {
+    /// Operator names and schedule durations, by worker and operator identifier.
+    schedules: BTreeMap<(WorkerIdentifier, usize), (String, Histogram)>,
+    /// Records on channels, by worker, channel, and whether sent.
+    channels: BTreeMap<(WorkerIdentifier, usize, bool), Volume>,
+    /// Time parked and number of parks, by worker.
+    parks: BTreeMap<WorkerIdentifier, (Duration, u64)>,
+    /// Bytes exchanged, by process, peer process, and whether sent.
+    peers: BTreeMap<(usize, usize, bool), Volume>,
+}
+
+/// A registry of metrics, shared by the sinks that update it.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsInner>>,
+}
+
+impl Metrics {
+    /// Creates a registry with no metrics.
+    pub fn new() -> Self { Self::default() }
+
+    /// Creates a sink for the "timely" log stream of a worker.
+    pub fn timely(&self) -> TimelyMetrics {
+        TimelyMetrics {
+            metrics: self.clone(),
+            names: HashMap::new(),
+            scheduled: HashMap::new(),
+            parked: HashMap::new(),
+        }
+    }
+
+    /// Creates a sink for the log stream of a communication thread.
+    pub fn communication(&self) -> CommunicationMetrics {
+        CommunicationMetrics { metrics: self.clone() }
+    }
+
+    fn lock(&self) -> MutexGuard<MetricsInner> {
+        // The metrics remain consistent even if a sink panicked while holding the lock.
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let inner = self.lock();
+        let mut text = String::new();
+
+        header(&mut text, "timely_operator_schedule_seconds", "histogram", "Duration of each scheduling of an operator.");
+        for ((worker, id), (name, histogram)) in inner.schedules.iter() {
+            let labels = format!("worker=\"{}\",operator=\"{}\",name=\"{}\"", worker, id, escape(name));
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(text, "timely_operator_schedule_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, cumulative);
+            }
+            let _ = writeln!(text, "timely_operator_schedule_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, histogram.count);
+            let _ = writeln!(text, "timely_operator_schedule_seconds_sum{{{}}} {}", labels, histogram.sum.as_secs_f64());
+            let _ = writeln!(text, "timely_operator_schedule_seconds_count{{{}}} {}", labels, histogram.count);
+        }
+
+        header(&mut text, "timely_channel_messages_total", "counter", "Messages sent or received on a channel.");
+        for ((worker, channel, send), volume) in inner.channels.iter() {
+            let _ = writeln!(text, "timely_channel_messages_total{{worker=\"{}\",channel=\"{}\",direction=\"{}\"}} {}", worker, channel, direction(*send), volume.messages);
+        }
+        header(&mut text, "timely_channel_records_total", "counter", "Records sent or received on a channel.");
+        for ((worker, channel, send), volume) in inner.channels.iter() {
+            let _ = writeln!(text, "timely_channel_records_total{{worker=\"{}\",channel=\"{}\",direction=\"{}\"}} {}", worker, channel, direction(*send), volume.amount);
+        }
+
+        header(&mut text, "timely_park_seconds_total", "counter", "Time a worker spent parked.");
+        for (worker, (parked, _)) in inner.parks.iter() {
+            let _ = writeln!(text, "timely_park_seconds_total{{worker=\"{}\"}} {}", worker, parked.as_secs_f64());
+        }
+        header(&mut text, "timely_parks_total", "counter", "Number of times a worker parked.");
+        for (worker, (_, parks)) in inner.parks.iter() {
+            let _ = writeln!(text, "timely_parks_total{{worker=\"{}\"}} {}", worker, parks);
+        }
+
+        header(&mut text, "timely_communication_bytes_total", "counter", "Bytes sent to or received from a peer process.");
+        for ((process, peer, send), volume) in inner.peers.iter() {
+            let _ = writeln!(text, "timely_communication_bytes_total{{process=\"{}\",peer=\"{}\",direction=\"{}\"}} {}", process, peer, direction(*send), volume.amount);
+        }
+        header(&mut text, "timely_communication_messages_total", "counter", "Messages sent to or received from a peer process.");
+        for ((process, peer, send), volume) in inner.peers.iter() {
+            let _ = writeln!(text, "timely_communication_messages_total{{process=\"{}\",peer=\"{}\",direction=\"{}\"}} {}", process, peer, direction(*send), volume.messages);
+        }
+
+        text
+    }
+
+    /// Answers each HTTP request to `listener` with the rendered metrics, from a new thread.
+    ///
+    /// The thread serves requests for the remainder of the process, one at a time. Scrapers that
+    /// take more than five seconds to send their request, or send more than 8KiB, are disconnected.
+    pub fn serve(&self, listener: TcpListener) -> JoinHandle<()> {
+        let metrics = self.clone();
+        std::thread::Builder::new()
+            .name("timely:metrics".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    // A failed scrape affects only the scraper, which may try again.
+                    if let Ok(mut stream) = stream {
+                        let _ = (|| -> std::io::Result<()> {
+                            stream.set_read_timeout(Some(SCRAPE_TIMEOUT))?;
+                            stream.set_write_timeout(Some(SCRAPE_TIMEOUT))?;
+                            read_request(&mut stream)?;
+                            let body = metrics.render();
+                            write!(
+                                stream,
+                                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                body.len(), body,
+                            )
+                        })();
+                    }
+                }
+            })
+            .expect("failed to spawn metrics thread")
+    }
+}
+
+/// Updates metrics from the "timely" log stream of one worker.
+pub struct TimelyMetrics {
+    metrics: Metrics,
+    /// Operator names, by worker and operator identifier.
+    names: HashMap<(WorkerIdentifier, usize), String>,
+    /// Start times of operators being scheduled, by worker and operator identifier.
+    scheduled: HashMap<(WorkerIdentifier, usize), Duration>,
+    /// Start times of parked workers.
+    parked: HashMap<WorkerIdentifier, Duration>,
+}
+
+impl TimelyMetrics {
+    /// Updates the metrics for a batch of logged events, draining the batch.
+    ///
+    /// This method has the signature of the actions accepted by the logging registry, apart from
+    /// the time, and is meant to be called from within such an action.
+    pub fn publish(&mut self, data: &mut Vec<(Duration, WorkerIdentifier, TimelyEvent)>) {
+        let mut inner = self.metrics.lock();
+        for (time, worker, event) in data.drain(..) {
+            match event {
+                TimelyEvent::Operates(event) => {
+                    self.names.insert((worker, event.id), event.name);
+                },
+                TimelyEvent::Schedule(event) => match event.start_stop {
+                    StartStop::Start => { self.scheduled.insert((worker, event.id), time); },
+                    StartStop::Stop => {
+                        if let Some(start) = self.scheduled.remove(&(worker, event.id)) {
+                            let (_, histogram) = inner.schedules.entry((worker, event.id)).or_insert_with(|| {
+                                let name = self.names.get(&(worker, event.id)).cloned().unwrap_or_else(|| "unknown".to_owned());
+                                (name, Histogram::default())
+                            });
+                            histogram.observe(time.saturating_sub(start));
+                        }
+                    },
+                },
+                TimelyEvent::Park(ParkEvent::Park(_)) => {
+                    self.parked.insert(worker, time);
+                },
+                TimelyEvent::Park(ParkEvent::Unpark) => {
+                    if let Some(start) = self.parked.remove(&worker) {
+                        let (parked, parks) = inner.parks.entry(worker).or_default();
+                        *parked += time.saturating_sub(start);
+                        *parks += 1;
+                    }
+                },
+                TimelyEvent::Messages(event) => {
+                    let volume = inner.channels.entry((worker, event.channel, event.is_send)).or_default();
+                    volume.messages += 1;
+                    volume.amount += event.length as u64;
+                },
+                _ => { },
+            }
+        }
+    }
+}
+
+/// Updates metrics from the log stream of one communication thread.
+pub struct CommunicationMetrics {
+    metrics: Metrics,
+}
+
+impl CommunicationMetrics {
+    /// Updates the metrics for a batch of logged events, draining the batch.
+    ///
+    /// Messages are attributed to the process and remote process of the thread that logged them.
+    pub fn publish(&mut self, data: &mut Vec<(Duration, CommunicationSetup, CommunicationEvent)>) {
+        let mut inner = self.metrics.lock();
+        for (_time, setup, event) in data.drain(..) {
+            if let (CommunicationEvent::Message(event), Some(remote)) = (event, setup.remote) {
+                let volume = inner.peers.entry((setup.process, remote, event.is_send)).or_default();
+                volume.messages += 1;
+                volume.amount += event.header.length as u64;
+            }
+        }
+    }
+}
+
+/// Writes the help and type lines of a metric.
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, kind);
+}
+
+fn direction(send: bool) -> &'static str {
+    if send { "send" } else { "recv" }
+}
+
+/// Escapes `text` for use as a label value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Reads an HTTP request up to the end of its headers, which are ignored.
+///
+/// Requests longer than `MAX_REQUEST` bytes are rejected with an error.
+fn read_request<R: Read>(reader: &mut R) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 { break; }
+        request.extend_from_slice(&buffer[.. read]);
+        if request.len() > MAX_REQUEST {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "metrics request too large"));
+        }
+    }
+    Ok(())
+}
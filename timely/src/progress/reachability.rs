@@ -72,7 +72,7 @@
 //! assert_eq!(results[2], ((Location::new_target(2, 0), 17), -1));
 //! ```
 
-use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::collections::{BinaryHeap, BTreeSet, HashMap, VecDeque};
 use std::cmp::Reverse;
 
 use crate::progress::Timestamp;
@@ -264,6 +264,154 @@ impl<T: Timestamp> Builder<T> {
     /// assert!(builder.is_acyclic());
     /// ```
     pub fn is_acyclic(&self) -> bool {
+        self.unreduced_locations().is_empty()
+    }
+
+    /// Reports cycles of default path summaries, each as a sequence of locations.
+    ///
+    /// Each cycle alternates between targets and sources: a target is connected to the
+    /// following source by a default summary internal to their operator, and a source is
+    /// connected to the following target by an edge. The last location is connected back to
+    /// the first. One cycle is reported for each strongly connected set of locations, and the
+    /// result is empty exactly when `is_acyclic` returns true.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::progress::{Location, Source, Target};
+    /// use timely::progress::reachability::Builder;
+    ///
+    /// // allocate a new empty topology builder.
+    /// let mut builder = Builder::<usize>::new();
+    ///
+    /// // Each node with one input connected to one output.
+    /// builder.add_node(0, 1, 1, vec![vec![Antichain::from_elem(0)]]);
+    /// builder.add_node(1, 1, 1, vec![vec![Antichain::from_elem(0)]]);
+    /// builder.add_node(2, 1, 1, vec![vec![Antichain::from_elem(1)]]);
+    ///
+    /// // Connect nodes 0 and 1 in a cycle, and node 2 to itself.
+    /// builder.add_edge(Source::new(0, 0), Target::new(1, 0));
+    /// builder.add_edge(Source::new(1, 0), Target::new(0, 0));
+    /// builder.add_edge(Source::new(2, 0), Target::new(2, 0));
+    ///
+    /// // Only the cycle through nodes 0 and 1 fails to advance timestamps.
+    /// let cycles = builder.cycles();
+    /// assert_eq!(cycles.len(), 1);
+    /// assert_eq!(cycles[0].len(), 4);
+    /// assert!(cycles[0].contains(&Location::new_source(0, 0)));
+    /// assert!(cycles[0].contains(&Location::new_target(1, 0)));
+    /// ```
+    pub fn cycles(&self) -> Vec<Vec<Location>> {
+
+        // Only locations that could not be reduced can be on cycles.
+        let mut remaining = self.unreduced_locations();
+
+        // Reverse adjacencies among the remaining locations.
+        let mut predecessors = HashMap::<Location, Vec<Location>>::new();
+        for location in remaining.iter() {
+            for successor in self.successors(*location) {
+                if remaining.contains(&successor) {
+                    predecessors.entry(successor).or_default().push(*location);
+                }
+            }
+        }
+
+        let mut cycles = Vec::new();
+        while let Some(start) = remaining.iter().next().cloned() {
+
+            // The strongly connected set of `start` are those locations it both reaches and is reached by.
+            let forward = Self::reachable(start, &remaining, |location| self.successors(location));
+            let backward = Self::reachable(start, &remaining, |location| predecessors.get(&location).cloned().unwrap_or_default());
+            let component = forward.intersection(&backward).cloned().collect::<BTreeSet<_>>();
+
+            // Locations downstream of a cycle are only reachable from themselves.
+            if component.len() > 1 {
+
+                // Breadth-first search from `start` back to itself, within the component.
+                let mut parents = HashMap::new();
+                let mut worklist = VecDeque::new();
+                worklist.push_back(start);
+                let mut last = None;
+                while let Some(location) = worklist.pop_front() {
+                    for successor in self.successors(location) {
+                        if successor == start {
+                            last = Some(location);
+                            worklist.clear();
+                            break;
+                        }
+                        if component.contains(&successor) && !parents.contains_key(&successor) {
+                            parents.insert(successor, location);
+                            worklist.push_back(successor);
+                        }
+                    }
+                }
+
+                let mut cycle = Vec::new();
+                let mut location = last.expect("strongly connected location must reach itself");
+                while location != start {
+                    cycle.push(location);
+                    location = parents[&location];
+                }
+                cycle.push(start);
+                cycle.reverse();
+                cycles.push(cycle);
+
+                for location in component.iter() {
+                    remaining.remove(location);
+                }
+            }
+
+            remaining.remove(&start);
+        }
+
+        cycles
+    }
+
+    /// Locations reachable from `start` through `step`, restricted to `within`.
+    fn reachable<I, F>(start: Location, within: &BTreeSet<Location>, mut step: F) -> BTreeSet<Location>
+    where
+        I: IntoIterator<Item=Location>,
+        F: FnMut(Location) -> I,
+    {
+        let mut reached = BTreeSet::new();
+        let mut worklist = vec![start];
+        reached.insert(start);
+        while let Some(location) = worklist.pop() {
+            for next in step(location) {
+                if within.contains(&next) && reached.insert(next) {
+                    worklist.push(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Locations immediately reachable from `location` without advancing timestamps.
+    fn successors(&self, location: Location) -> Vec<Location> {
+        match location.port {
+            Port::Source(port) => {
+                self.edges[location.node][port]
+                    .iter()
+                    .map(|target| Location::from(*target))
+                    .collect()
+            },
+            Port::Target(port) => {
+                self.nodes[location.node][port]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, summaries)| summaries.elements().iter().any(|summary| summary == &Default::default()))
+                    .map(|(output, _)| Location::new_source(location.node, output))
+                    .collect()
+            },
+        }
+    }
+
+    /// Locations that remain after repeatedly removing those without default-summary predecessors.
+    ///
+    /// The result is empty exactly when the graph has no cycles of default path summaries. Otherwise,
+    /// it contains the locations on such cycles, and those reachable from them.
+    fn unreduced_locations(&self) -> BTreeSet<Location> {
 
         let locations = self.shape.iter().map(|(targets, sources)| targets + sources).sum();
         let mut in_degree = HashMap::with_capacity(locations);
@@ -339,7 +487,7 @@ impl<T: Timestamp> Builder<T> {
         }
 
         // Acyclic graphs should reduce to empty collections.
-        in_degree.is_empty()
+        in_degree.into_keys().collect()
     }
 }
 
@@ -405,6 +553,60 @@ pub struct Tracker<T:Timestamp> {
 
     /// Optionally, a unique logging identifier and logging for tracking events.
     logger: Option<logging::TrackerLogger>,
+
+    /// Whether to check updates for violations of the progress protocol.
+    validate: bool,
+    /// Targets whose counts changed in the current propagation, when validating.
+    validate_targets: Vec<(Target, T)>,
+    /// Violations discovered but not yet reported.
+    violations: Vec<Violation<T>>,
+}
+
+/// A violation of the progress protocol observed by a validating `Tracker`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation<T> {
+    /// The accumulated capability count at an operator output became negative.
+    NegativeCapability {
+        /// The operator output holding the capability.
+        source: Source,
+        /// The time of the capability.
+        time: T,
+        /// The accumulated (negative) count.
+        count: i64,
+    },
+    /// More messages were consumed at an operator input than were produced for it,
+    /// and no outstanding capability could produce the difference.
+    UnproducedMessages {
+        /// The operator input consuming the messages.
+        target: Target,
+        /// The time of the messages.
+        time: T,
+        /// The accumulated (negative) count.
+        count: i64,
+    },
+}
+
+impl<T> Violation<T> {
+    /// The index of the node at which the violation occurred.
+    pub fn node(&self) -> usize {
+        match self {
+            Violation::NegativeCapability { source, .. } => source.node,
+            Violation::UnproducedMessages { target, .. } => target.node,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Display for Violation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Violation::NegativeCapability { source, time, count } => {
+                write!(f, "capability count {} at time {:?} on output {}", count, time, source.port)
+            },
+            Violation::UnproducedMessages { target, time, count } => {
+                write!(f, "{} more messages consumed than produced at time {:?} on input {}", -count, time, target.port)
+            },
+        }
+    }
 }
 
 /// Target and source information for each operator.
@@ -556,6 +758,9 @@ impl<T:Timestamp> Tracker<T> {
             output_changes,
             total_counts: 0,
             logger,
+            validate: false,
+            validate_targets: Vec::new(),
+            violations: Vec::new(),
         };
 
         (tracker, builder_summary)
@@ -599,6 +804,10 @@ impl<T:Timestamp> Tracker<T> {
         // witness that frontier.
         for ((target, time), diff) in self.target_changes.drain() {
 
+            if self.validate {
+                self.validate_targets.push((target, time.clone()));
+            }
+
             let operator = &mut self.per_operator[target.node].targets[target.port];
             let changes = operator.pointstamps.update_iter(Some((time, diff)));
 
@@ -619,6 +828,16 @@ impl<T:Timestamp> Tracker<T> {
         for ((source, time), diff) in self.source_changes.drain() {
 
             let operator = &mut self.per_operator[source.node].sources[source.port];
+
+            // Capabilities are never transiently negative, as each worker reports its
+            // own capabilities in order.
+            if self.validate {
+                let count = operator.pointstamps.count_for(&time) + diff;
+                if count < 0 {
+                    self.violations.push(Violation::NegativeCapability { source, time: time.clone(), count });
+                }
+            }
+
             let changes = operator.pointstamps.update_iter(Some((time, diff)));
 
             for (time, diff) in changes {
@@ -699,6 +918,35 @@ impl<T:Timestamp> Tracker<T> {
                 };
             }
         }
+
+        // Step 3: Check that no message deficit at a target is left uncovered.
+        //
+        // Message counts can be transiently negative, when a consumer reports before
+        // the producer. A producer reports its messages along with the release of the
+        // capability that produced them, so a deficit is only an error once nothing
+        // upstream could still produce messages at that time.
+        for (target, time) in self.validate_targets.drain(..) {
+            let port = &self.per_operator[target.node].targets[target.port];
+            let count = port.pointstamps.count_for(&time);
+            if count < 0 && !port.implications.less_equal(&time) {
+                self.violations.push(Violation::UnproducedMessages { target, time, count });
+            }
+        }
+    }
+
+    /// Enables or disables validation of progress updates.
+    ///
+    /// When enabled, the tracker checks each propagated update for violations of the
+    /// progress protocol, which can then be retrieved with `violations`.
+    pub fn set_validate(&mut self, validate: bool) {
+        self.validate = validate;
+    }
+
+    /// Drains the protocol violations discovered since the last call.
+    ///
+    /// Violations are only discovered if validation is enabled with `set_validate`.
+    pub fn violations(&mut self) -> ::std::vec::Drain<'_, Violation<T>> {
+        self.violations.drain(..)
     }
 
     /// Implications of maintained capabilities projected to each output.
@@ -9,6 +9,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
+use std::time::Instant;
 
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelyProgressLogger as ProgressLogger;
@@ -16,7 +17,8 @@ use crate::logging::TimelyProgressLogger as ProgressLogger;
 use crate::scheduling::Schedule;
 use crate::scheduling::activate::Activations;
 
-use crate::progress::frontier::{Antichain, MutableAntichain, MutableAntichainFilter};
+use crate::order::PartialOrder;
+use crate::progress::frontier::{Antichain, AntichainRef, MutableAntichain, MutableAntichainFilter};
 use crate::progress::{Timestamp, Operate, operate::SharedProgress};
 use crate::progress::{Location, Port, Source, Target};
 
@@ -26,6 +28,7 @@ use crate::progress::reachability;
 use crate::progress::timestamp::Refines;
 
 use crate::worker::ProgressMode;
+use crate::utilization::OperatorCounter;
 
 // IMPORTANT : by convention, a child identifier of zero is used to indicate inputs and outputs of
 // the Subgraph itself. An identifier greater than zero corresponds to an actual child, which can
@@ -56,6 +59,9 @@ where
 
     edge_stash: Vec<(Source, Target)>,
 
+    // indices of children that could not be built, whose edges are discarded.
+    placeholders: Vec<usize>,
+
     // shared state written to by the datapath, counting records entering this subgraph instance.
     input_messages: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
 
@@ -116,6 +122,7 @@ where
             children,
             child_count: 1,
             edge_stash: Vec::new(),
+            placeholders: Vec::new(),
             input_messages: Vec::new(),
             output_capabilities: Vec::new(),
             logging,
@@ -143,8 +150,37 @@ where
         self.children.push(PerOperatorState::new(child, index, self.path.clone(), identifier, self.logging.clone()))
     }
 
+    /// Occupies `index` with an operator that does nothing, in place of a child that could not be built.
+    ///
+    /// Edges to and from the child, for example from streams that entered or left it, are discarded
+    /// as the subgraph is built.
+    pub(crate) fn add_placeholder(&mut self, index: usize) {
+        self.placeholders.push(index);
+        let mut placeholder = PerOperatorState::empty(0, 0);
+        placeholder.name = "Placeholder".to_owned();
+        placeholder.index = index;
+        placeholder.address = self.path.clone();
+        placeholder.address.push(index);
+        self.children.push(placeholder);
+    }
+
     /// Now that initialization is complete, actually build a subgraph.
-    pub fn build<A: crate::worker::AsWorker>(mut self, worker: &mut A) -> Subgraph<TOuter, TInner> {
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the subgraph contains a cycle along which timestamps do not
+    /// strictly advance. Use `try_build` to receive a description of the cycles instead.
+    pub fn build<A: crate::worker::AsWorker>(self, worker: &mut A) -> Subgraph<TOuter, TInner> {
+        self.try_build(worker).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Now that initialization is complete, attempt to build a subgraph.
+    ///
+    /// The subgraph is checked for cycles along which the summed path summaries do not strictly
+    /// advance timestamps, for example a `feedback` constructed with a default summary. Such
+    /// cycles would prevent frontiers from ever advancing past the times in the cycle. If any
+    /// exist, the returned error names the operators and channels in each of them.
+    pub fn try_build<A: crate::worker::AsWorker>(mut self, worker: &mut A) -> Result<Subgraph<TOuter, TInner>, String> {
         // at this point, the subgraph is frozen. we should initialize any internal state which
         // may have been determined after construction (e.g. the numbers of inputs and outputs).
         // we also need to determine what to return as a summary and initial capabilities, which
@@ -168,18 +204,40 @@ where
             builder.add_node(index, child.inputs, child.outputs, child.internal_summary.clone());
         }
 
-        for (source, target) in self.edge_stash {
+        let placeholders = &self.placeholders;
+        self.edge_stash.retain(|(source, target)| !placeholders.contains(&source.node) && !placeholders.contains(&target.node));
+        for (source, target) in self.edge_stash.drain(..) {
             self.children[source.node].edges[source.port].push(target);
             builder.add_edge(source, target);
         }
 
+        let cycles = builder.cycles();
+        if !cycles.is_empty() {
+            return Err(self.describe_cycles(&cycles));
+        }
+
         // The `None` argument is optional logging infrastructure.
         let path = self.path.clone();
         let reachability_logging =
         worker.log_register()
             .get::<reachability::logging::TrackerEvent>("timely/reachability")
             .map(|logger| reachability::logging::TrackerLogger::new(path, logger));
-        let (tracker, scope_summary) = builder.build(reachability_logging);
+        let (mut tracker, scope_summary) = builder.build(reachability_logging);
+
+        let validate_progress = worker.config().validate_progress;
+        if validate_progress {
+            tracker.set_validate(true);
+            for child in self.children.iter_mut().skip(1) {
+                child.enable_validation();
+            }
+        }
+
+        let counters = worker.utilization_counters();
+        for child in self.children.iter_mut().skip(1) {
+            child.counter = Some(counters.borrow_mut().operator(&child.address[..], &child.name));
+            #[cfg(feature = "tracing")]
+            { child.worker_index = worker.index(); }
+        }
 
         let progcaster = Progcaster::new(worker, &self.path, self.logging.clone(), self.progress_logging.clone());
 
@@ -191,7 +249,7 @@ where
 
         activations.borrow_mut().activate(&self.path[..]);
 
-        Subgraph {
+        Ok(Subgraph {
             name: self.name,
             path: self.path,
             inputs,
@@ -214,7 +272,42 @@ where
             scope_summary,
 
             progress_mode: worker.config().progress_mode,
+            validate_progress,
+
+            logging: self.logging,
+            frontier_changes: Vec::new(),
+            input_history: Vec::new(),
+        })
+    }
+
+    /// Describes cycles of locations, as reported by `reachability::Builder::cycles`.
+    fn describe_cycles(&self, cycles: &[Vec<Location>]) -> String {
+        let describe = |node: usize| {
+            let child = &self.children[node];
+            format!("{:?} (address {:?})", child.name, child.address)
+        };
+        let mut result = format!(
+            "Scope {:?} (address {:?}) contains {} cycle(s) whose path summaries do not advance timestamps:",
+            self.name,
+            self.path,
+            cycles.len(),
+        );
+        for (index, cycle) in cycles.iter().enumerate() {
+            result.push_str(&format!("\n  cycle {}:", index));
+            for (position, location) in cycle.iter().enumerate() {
+                let next = cycle[(position + 1) % cycle.len()];
+                match (location.port, next.port) {
+                    (Port::Target(input), Port::Source(output)) => {
+                        result.push_str(&format!("\n    operator {}: input {} to output {}", describe(location.node), input, output));
+                    },
+                    (Port::Source(output), Port::Target(input)) => {
+                        result.push_str(&format!("\n    channel from {} output {} to {} input {}", describe(location.node), output, describe(next.node), input));
+                    },
+                    _ => unreachable!("cycles alternate between targets and sources"),
+                }
+            }
         }
+        result
     }
 }
 
@@ -266,6 +359,13 @@ where
     scope_summary: Vec<Vec<Antichain<TInner::Summary>>>,
 
     progress_mode: ProgressMode,
+    validate_progress: bool,
+
+    logging: Option<Logger>,
+    // children whose input frontiers changed, maintained only when logging.
+    frontier_changes: Vec<usize>,
+    // times at which the frontier of the scope's inputs changed, maintained only when logging.
+    input_history: Vec<(Instant, Antichain<TInner>)>,
 }
 
 impl<TOuter, TInner> Schedule for Subgraph<TOuter, TInner>
@@ -345,6 +445,10 @@ where
 
         let incomplete = child.schedule();
 
+        if self.validate_progress {
+            child.check_progress();
+        }
+
         if incomplete != self.incomplete[child_index] {
             if incomplete { self.incomplete_count += 1; }
             else          { self.incomplete_count -= 1; }
@@ -461,11 +565,24 @@ where
         // Propagate implications of progress changes.
         self.pointstamp_tracker.propagate_all();
 
+        if let Some(violation) = self.pointstamp_tracker.violations().next() {
+            let child = &self.children[violation.node()];
+            panic!(
+                "Progress violation at operator {:?} (address {:?}): {}",
+                child.name,
+                child.address,
+                violation,
+            );
+        }
+
         // Drain propagated information into shared progress structure.
         for ((location, time), diff) in self.pointstamp_tracker.pushed().drain() {
             self.maybe_shutdown.push(location.node);
             // Targets are actionable, sources are not.
             if let crate::progress::Port::Target(port) = location.port {
+                if self.logging.is_some() {
+                    self.frontier_changes.push(location.node);
+                }
                 if self.children[location.node].notify {
                     self.temp_active.push(Reverse(location.node));
                 }
@@ -480,6 +597,8 @@ where
             }
         }
 
+        self.log_frontiers();
+
         // Consider scheduling each recipient of progress information to shut down.
         self.maybe_shutdown.sort();
         self.maybe_shutdown.dedup();
@@ -503,6 +622,72 @@ where
         }
     }
 
+    /// Logs the input frontiers of children, and how far they lag the inputs of the scope.
+    ///
+    /// A child is logged if its input frontiers changed, or if the inputs of the scope advanced
+    /// while the child lags them. The lag of a frontier is the time since the inputs of the scope
+    /// first advanced beyond it, which requires a history of the inputs of the scope back to the
+    /// frontier of the furthest lagging child.
+    fn log_frontiers(&mut self) {
+        let logging = match self.logging.as_ref() {
+            Some(logging) => logging,
+            None => return,
+        };
+
+        let mut inputs = Antichain::new();
+        for (index, child) in self.children.iter().enumerate() {
+            if index == 0 || child.inputs == 0 {
+                for source in self.pointstamp_tracker.node_state(index).sources.iter() {
+                    inputs.extend(source.pointstamps.frontier().iter().cloned());
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let advanced = self.input_history.last().map(|(_, last)| last != &inputs).unwrap_or(true);
+        if advanced {
+            self.input_history.push((now, inputs));
+        }
+        else if self.frontier_changes.is_empty() {
+            return;
+        }
+
+        self.frontier_changes.sort();
+        self.frontier_changes.dedup();
+        let history = &self.input_history[..];
+        let tracker = &self.pointstamp_tracker;
+        // The index of the first inputs of the scope beyond `frontier`, if any.
+        let lagging = |frontier: AntichainRef<TInner>| {
+            history.iter().position(|(_, inputs)| !PartialOrder::less_equal(&inputs.borrow(), &frontier))
+        };
+
+        // Retained history starts at the inputs the furthest lagging child has yet to reach.
+        let mut retain = history.len() - 1;
+        for index in 1 .. self.children.len() {
+            let changed = self.frontier_changes.binary_search(&index).is_ok();
+            if !changed && !advanced { continue; }
+            let targets = &tracker.node_state(index).targets;
+            let positions: Vec<Option<usize>> = targets.iter().map(|target| lagging(target.implications.frontier())).collect();
+            if changed || positions.iter().any(|position| position.is_some()) {
+                logging.log(crate::logging::FrontierEvent {
+                    id: self.children[index].id,
+                    frontiers: targets.iter().map(|target| target.implications.frontier().iter().map(|time| format!("{:?}", time)).collect()).collect(),
+                    inputs: history[history.len() - 1].1.iter().map(|time| format!("{:?}", time)).collect(),
+                    lag: positions.iter().map(|position| position.map(|position| now.duration_since(history[position].0)).unwrap_or_default()).collect(),
+                });
+            }
+            if advanced {
+                for position in positions.into_iter().flatten() {
+                    retain = std::cmp::min(retain, position);
+                }
+            }
+        }
+        self.frontier_changes.clear();
+        if advanced {
+            self.input_history.drain(.. retain);
+        }
+    }
+
     /// Sends local progress updates to all workers.
     ///
     /// This method does not guarantee that all of `self.local_pointstamps` are
@@ -595,6 +780,7 @@ struct PerOperatorState<T: Timestamp> {
     name: String,       // name of the operator
     index: usize,       // index of the operator within its parent scope
     id: usize,          // worker-unique identifier
+    address: Vec<usize>,    // path from the root to the operator
 
     local: bool,        // indicates whether the operator will exchange data or not
     notify: bool,
@@ -609,7 +795,15 @@ struct PerOperatorState<T: Timestamp> {
 
     internal_summary: Vec<Vec<Antichain<T::Summary>>>,   // cached result from get_internal_summary.
 
+    // capabilities reported at each output, maintained only when validating progress.
+    held: Option<Vec<MutableAntichain<T>>>,
+
     logging: Option<Logger>,
+
+    counter: Option<OperatorCounter>,   // utilization counters of the operator.
+
+    #[cfg(feature = "tracing")]
+    worker_index: usize,                // index of the worker, to tag tracing spans.
 }
 
 impl<T: Timestamp> PerOperatorState<T> {
@@ -620,6 +814,7 @@ impl<T: Timestamp> PerOperatorState<T> {
             operator:   None,
             index:      0,
             id:         usize::max_value(),
+            address:    Vec::new(),
             local:      false,
             notify:     true,
             inputs,
@@ -631,13 +826,17 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(inputs,outputs))),
             internal_summary: Vec::new(),
+            held: None,
+            counter: None,
+            #[cfg(feature = "tracing")]
+            worker_index: 0,
         }
     }
 
     pub fn new(
         mut scope: Box<dyn Operate<T>>,
         index: usize,
-        mut _path: Vec<usize>,
+        mut path: Vec<usize>,
         identifier: usize,
         logging: Option<Logger>
     ) -> PerOperatorState<T>
@@ -661,11 +860,14 @@ impl<T: Timestamp> PerOperatorState<T> {
             "operator summary had too few outputs",
         );
 
+        path.push(index);
+
         PerOperatorState {
             name:               scope.name().to_owned(),
             operator:           Some(scope),
             index,
             id:                 identifier,
+            address:            path,
             local,
             notify,
             inputs,
@@ -676,6 +878,10 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             shared_progress,
             internal_summary,
+            held:               None,
+            counter:            None,
+            #[cfg(feature = "tracing")]
+            worker_index:       0,
         }
     }
 
@@ -696,7 +902,22 @@ impl<T: Timestamp> PerOperatorState<T> {
                 l.log(crate::logging::ScheduleEvent::start(self.id));
             }
 
-            let incomplete = operator.schedule();
+            let start = self.counter.as_ref().map(|_| Instant::now());
+            let incomplete = {
+                // Spans of operators in nested scopes are nested in the span of their scope.
+                #[cfg(feature = "tracing")]
+                let _span = tracing::trace_span!(
+                    "schedule",
+                    operator = %self.name,
+                    address = ?self.address,
+                    id = self.id,
+                    worker = self.worker_index,
+                ).entered();
+                operator.schedule()
+            };
+            if let (Some(counter), Some(start)) = (self.counter.as_ref(), start) {
+                counter.record(start.elapsed());
+            }
 
             // Perhaps log information about the stop of the schedule call.
             if let Some(l) = self.logging.as_mut() {
@@ -742,6 +963,9 @@ impl<T: Timestamp> PerOperatorState<T> {
             }
         }
         for (output, internal) in shared_progress.internals.iter_mut().enumerate() {
+            if let Some(held) = self.held.as_mut() {
+                held[output].update_iter(internal.iter().cloned());
+            }
             let source = Location::new_source(self.index, output);
             for (time, delta) in internal.drain() {
                 pointstamps.update((source, time.clone()), delta);
@@ -757,6 +981,79 @@ impl<T: Timestamp> PerOperatorState<T> {
         }
     }
 
+    /// Starts tracking reported capabilities, so that progress can be checked.
+    fn enable_validation(&mut self) {
+        self.held = Some(vec![MutableAntichain::new(); self.outputs]);
+    }
+
+    /// Checks `self.shared_progress` for violations of the progress protocol.
+    ///
+    /// Consumed and produced counts must be positive, and capability counts must not
+    /// become negative. For operators reporting worker-local progress we also check that
+    /// capabilities are only acquired and messages only produced at times for which the
+    /// operator held a capability or consumed a message. Non-local operators, like nested
+    /// scopes, report progress on behalf of all workers, and are not subject to this check.
+    ///
+    /// Panics with the name and address of the operator on the first violation.
+    fn check_progress(&self) {
+
+        let shared_progress = &mut *self.shared_progress.borrow_mut();
+        let held = match self.held.as_ref() {
+            Some(held) => held,
+            None => return,
+        };
+
+        for (input, consumed) in shared_progress.consumeds.iter_mut().enumerate() {
+            for (time, diff) in consumed.iter() {
+                if *diff < 0 {
+                    self.violation(format!("{} messages consumed at time {:?} on input {}", diff, time, input));
+                }
+            }
+        }
+        for (output, produced) in shared_progress.produceds.iter_mut().enumerate() {
+            for (time, diff) in produced.iter() {
+                if *diff < 0 {
+                    self.violation(format!("{} messages produced at time {:?} on output {}", diff, time, output));
+                }
+            }
+        }
+        for (output, internal) in shared_progress.internals.iter_mut().enumerate() {
+            for (time, diff) in internal.iter() {
+                let count = held[output].count_for(time) + diff;
+                if count < 0 {
+                    self.violation(format!("capability count {} at time {:?} on output {}", count, time, output));
+                }
+            }
+        }
+
+        if self.local {
+            let consumeds = &mut shared_progress.consumeds;
+            let mut supported = |output: usize, time: &T| {
+                held[output].less_equal(time) ||
+                consumeds.iter_mut().any(|x| x.iter().any(|(t,d)| *d > 0 && t.less_equal(time)))
+            };
+            for (output, internal) in shared_progress.internals.iter_mut().enumerate() {
+                for (time, diff) in internal.iter() {
+                    if *diff > 0 && !supported(output, time) {
+                        self.violation(format!("capability acquired at time {:?} on output {} without a capability or message to derive it from", time, output));
+                    }
+                }
+            }
+            for (output, produced) in shared_progress.produceds.iter_mut().enumerate() {
+                for (time, diff) in produced.iter() {
+                    if *diff > 0 && !supported(output, time) {
+                        self.violation(format!("{} messages produced at time {:?} on output {} without a capability", diff, time, output));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reports a violation of the progress protocol by this operator.
+    fn violation(&self, message: String) -> ! {
+        panic!("Progress violation at operator {:?} (address {:?}): {}", self.name, self.address, message);
+    }
+
     /// Test the validity of `self.shared_progress`.
     ///
     /// The validity of shared progress information depends on both the external frontiers and the
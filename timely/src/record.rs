@@ -0,0 +1,425 @@
+//! Recording and replaying the execution of a worker.
+//!
+//! A worker records its execution with [Worker::record], which writes to a recording the
+//! messages it receives on each channel, the set of activated operators and the order in
+//! which it schedules dataflows at each step, and the interactions with each input handle
+//! registered with [Recorder::record_input]. Together these determine the execution of the
+//! worker, assuming its operators are deterministic.
+//!
+//! A recording is replayed by [Replay], on a single thread and without other workers. The
+//! replaying worker is constructed with the allocator of the replay, which reports the index
+//! and peers of the recorded worker, presents the recorded messages on each channel, and
+//! discards sent messages. The same dataflows must then be constructed as in the recorded
+//! execution, their input handles handed to the replay in the order they were recorded, after
+//! which the replay drives the worker through the recorded steps. This allows one worker of a
+//! nondeterministic multi-worker execution to be reproduced, for example under a debugger.
+//!
+//! Recordings are written with `abomonation`, and must be replayed by the same program.
+//! Control messages among workers are not recorded, and dataflows dropped by other workers
+//! are not dropped in the replay.
+//!
+//! # Examples
+//! ```
+//! use timely::WorkerConfig;
+//! use timely::communication::Allocate;
+//! use timely::dataflow::{InputHandle, ProbeHandle};
+//! use timely::dataflow::operators::{Input, Exchange, Probe};
+//! use timely::record::Replay;
+//! use timely::worker::Worker;
+//!
+//! fn build<A: Allocate>(worker: &mut Worker<A>) -> (InputHandle<u64, u64>, ProbeHandle<u64>) {
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input).exchange(|x| *x).probe()
+//!     });
+//!     (input, probe)
+//! }
+//!
+//! let path = std::env::temp_dir().join(format!("timely-record-doc-{}", std::process::id()));
+//! let path2 = path.clone();
+//!
+//! // Record the execution of worker 1 of two workers.
+//! timely::execute(timely::Config::process(2), move |worker| {
+//!     let recorder = if worker.index() == 1 {
+//!         Some(worker.record(std::fs::File::create(&path2).unwrap()))
+//!     } else { None };
+//!     let (mut input, probe) = build(worker);
+//!     if let Some(recorder) = &recorder {
+//!         recorder.record_input(&mut input);
+//!     }
+//!     for round in 0 .. 10 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//! }).unwrap();
+//!
+//! // Replay the execution of worker 1 on this thread.
+//! let mut replay = Replay::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+//! let mut worker = Worker::new(WorkerConfig::default(), replay.allocator());
+//! let (input, probe) = build(&mut worker);
+//! replay.replay_input(input);
+//! replay.run(&mut worker);
+//! assert!(!probe.less_than(&10));
+//! std::fs::remove_file(&path).ok();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+use crate::bytes::arc::Bytes;
+use crate::communication::{Allocate, Data, Message, Pull, Push};
+use crate::dataflow::InputHandleCore;
+use crate::progress::Timestamp;
+use crate::worker::Worker;
+use crate::Container;
+
+/// An event in the recorded execution of a worker.
+#[derive(Abomonation, Debug, Clone, PartialEq, Eq)]
+pub enum RecordEvent {
+    /// The index and number of peers of the recorded worker.
+    Worker {
+        /// The index of the worker.
+        index: usize,
+        /// The number of workers.
+        peers: usize,
+    },
+    /// A step that scheduled dataflows.
+    Step {
+        /// The activated paths at the start of the step.
+        active: Vec<Vec<usize>>,
+        /// The dataflows stepped, in the order they were stepped.
+        stepped: Vec<usize>,
+    },
+    /// A message received by pulling from a channel.
+    ///
+    /// Pulls that receive no message are not recorded. Messages are recorded before the step
+    /// in which they were received, and are presented to the replay from that step on.
+    Pull {
+        /// The channel identifier.
+        channel: usize,
+        /// The binary representation of the received message.
+        message: Vec<u8>,
+    },
+    /// A batch of records introduced by an input handle.
+    Send {
+        /// The index of the input handle, in the order of registration.
+        input: usize,
+        /// The binary representation of the batch.
+        batch: Vec<u8>,
+    },
+    /// An input handle advanced its time.
+    Advance {
+        /// The index of the input handle, in the order of registration.
+        input: usize,
+        /// The binary representation of the new time.
+        time: Vec<u8>,
+    },
+    /// An input handle was closed.
+    Close {
+        /// The index of the input handle, in the order of registration.
+        input: usize,
+    },
+}
+
+/// Writes the recording of a worker's execution.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Rc<RefCell<RecorderInner>>,
+}
+
+struct RecorderInner {
+    writer: Box<dyn Write>,
+    inputs: usize,
+}
+
+impl Recorder {
+    /// Creates a recorder for the worker with `index` out of `peers`, writing to `writer`.
+    pub(crate) fn new<W: Write+'static>(writer: W, index: usize, peers: usize) -> Self {
+        let recorder = Recorder {
+            inner: Rc::new(RefCell::new(RecorderInner {
+                writer: Box::new(writer),
+                inputs: 0,
+            }))
+        };
+        recorder.record(&RecordEvent::Worker { index, peers });
+        recorder
+    }
+
+    /// Appends `event` to the recording.
+    pub(crate) fn record(&self, event: &RecordEvent) {
+        // TODO: recording has no mechanism to report errors, so we `expect`.
+        let mut inner = self.inner.borrow_mut();
+        unsafe { ::abomonation::encode(event, &mut inner.writer).expect("Recording write failed"); }
+    }
+
+    /// Records the interactions with `handle`.
+    ///
+    /// The handle is identified by the order in which handles are registered, which must be
+    /// the order in which they are later presented to [Replay::replay_input].
+    pub fn record_input<T, C>(&self, handle: &mut InputHandleCore<T, C>)
+    where
+        T: Timestamp+Data,
+        C: Container+Data,
+    {
+        let input = {
+            let mut inner = self.inner.borrow_mut();
+            inner.inputs += 1;
+            inner.inputs - 1
+        };
+        handle.set_recorder(InputRecorder {
+            input,
+            recorder: self.clone(),
+            encode_time: encode::<T>,
+            encode_batch: encode::<C>,
+        });
+    }
+
+    /// Flushes the recording to its writer.
+    pub fn flush(&self) {
+        self.inner.borrow_mut().writer.flush().expect("Recording flush failed");
+    }
+}
+
+impl Drop for RecorderInner {
+    fn drop(&mut self) {
+        self.writer.flush().ok();
+    }
+}
+
+/// Records the interactions with an input handle.
+pub(crate) struct InputRecorder<T, C> {
+    input: usize,
+    recorder: Recorder,
+    encode_time: fn(&T) -> Vec<u8>,
+    encode_batch: fn(&C) -> Vec<u8>,
+}
+
+impl<T, C> InputRecorder<T, C> {
+    /// Records that `batch` was introduced.
+    pub(crate) fn send(&self, batch: &C) {
+        let batch = (self.encode_batch)(batch);
+        self.recorder.record(&RecordEvent::Send { input: self.input, batch });
+    }
+    /// Records that the input advanced to `time`.
+    pub(crate) fn advance(&self, time: &T) {
+        let time = (self.encode_time)(time);
+        self.recorder.record(&RecordEvent::Advance { input: self.input, time });
+    }
+    /// Records that the input was closed.
+    pub(crate) fn close(&self) {
+        self.recorder.record(&RecordEvent::Close { input: self.input });
+    }
+}
+
+impl<T, C> fmt::Debug for InputRecorder<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InputRecorder")
+            .field("input", &self.input)
+            .finish()
+    }
+}
+
+/// Wraps a puller, recording each received message.
+pub(crate) struct RecordPuller<T> {
+    channel: usize,
+    puller: Box<dyn Pull<Message<T>>>,
+    recorder: Recorder,
+}
+
+impl<T> RecordPuller<T> {
+    /// Wraps `puller` for the channel `channel`, recording to `recorder`.
+    pub(crate) fn new(channel: usize, puller: Box<dyn Pull<Message<T>>>, recorder: Recorder) -> Self {
+        RecordPuller { channel, puller, recorder }
+    }
+}
+
+impl<T: Data> Pull<Message<T>> for RecordPuller<T> {
+    fn pull(&mut self) -> &mut Option<Message<T>> {
+        let result = self.puller.pull();
+        if let Some(message) = result.as_ref() {
+            let mut bytes = Vec::new();
+            message.into_bytes(&mut bytes);
+            self.recorder.record(&RecordEvent::Pull { channel: self.channel, message: bytes });
+        }
+        result
+    }
+}
+
+/// Replays the recorded execution of a worker.
+pub struct Replay {
+    index: usize,
+    peers: usize,
+    pulls: Rc<RefCell<HashMap<usize, VecDeque<Vec<u8>>>>>,
+    events: VecDeque<RecordEvent>,
+    inputs: Vec<Option<Box<dyn ReplayInput>>>,
+}
+
+impl Replay {
+    /// Reads a recording from `reader`.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut index_peers = None;
+        let mut events = VecDeque::new();
+
+        let mut remaining = &mut bytes[..];
+        while !remaining.is_empty() {
+            let (event, rest) = unsafe { ::abomonation::decode::<RecordEvent>(remaining) }
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed recording"))?;
+            match event.clone() {
+                RecordEvent::Worker { index, peers } => { index_peers = Some((index, peers)); },
+                event => { events.push_back(event); },
+            }
+            remaining = rest;
+        }
+
+        let (index, peers) = index_peers.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "recording lacks worker"))?;
+        Ok(Replay {
+            index,
+            peers,
+            pulls: Rc::new(RefCell::new(HashMap::new())),
+            events,
+            inputs: Vec::new(),
+        })
+    }
+
+    /// An allocator presenting the recorded messages, with which to construct the replaying worker.
+    pub fn allocator(&self) -> ReplayAllocator {
+        ReplayAllocator {
+            index: self.index,
+            peers: self.peers,
+            events: Rc::new(RefCell::new(Vec::new())),
+            pulls: self.pulls.clone(),
+        }
+    }
+
+    /// Registers an input handle, to be driven as the recorded handle of the same index.
+    pub fn replay_input<T, C>(&mut self, handle: InputHandleCore<T, C>)
+    where
+        T: Timestamp+Data,
+        C: Container+Data,
+    {
+        self.inputs.push(Some(Box::new(handle)));
+    }
+
+    /// Replays the recording up to and including its next step.
+    ///
+    /// Returns `false` once the recording is exhausted.
+    pub fn step<A: Allocate>(&mut self, worker: &mut Worker<A>) -> bool {
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                RecordEvent::Step { active, stepped } => {
+                    worker.step_recorded(&active[..], &stepped[..]);
+                    return true;
+                },
+                RecordEvent::Send { input, batch } => { self.input(input).send(batch); },
+                RecordEvent::Advance { input, time } => { self.input(input).advance(time); },
+                RecordEvent::Close { input } => { self.inputs[input] = None; },
+                RecordEvent::Pull { channel, message } => {
+                    self.pulls.borrow_mut().entry(channel).or_default().push_back(message);
+                },
+                RecordEvent::Worker { .. } => { },
+            }
+        }
+        false
+    }
+
+    /// Replays the entire recording.
+    pub fn run<A: Allocate>(&mut self, worker: &mut Worker<A>) {
+        while self.step(worker) { }
+    }
+
+    fn input(&mut self, input: usize) -> &mut Box<dyn ReplayInput> {
+        self.inputs
+            .get_mut(input)
+            .and_then(|input| input.as_mut())
+            .expect("Recorded input not registered for replay")
+    }
+}
+
+/// An input handle driven by a replay.
+trait ReplayInput {
+    fn send(&mut self, batch: Vec<u8>);
+    fn advance(&mut self, time: Vec<u8>);
+}
+
+impl<T: Timestamp+Data, C: Container+Data> ReplayInput for InputHandleCore<T, C> {
+    fn send(&mut self, batch: Vec<u8>) {
+        self.send_batch(&mut decode::<C>(batch));
+    }
+    fn advance(&mut self, time: Vec<u8>) {
+        self.advance_to(decode::<T>(time));
+    }
+}
+
+/// An allocator presenting the messages of a recording.
+///
+/// Messages sent on its channels are discarded.
+pub struct ReplayAllocator {
+    index: usize,
+    peers: usize,
+    events: Rc<RefCell<Vec<usize>>>,
+    pulls: Rc<RefCell<HashMap<usize, VecDeque<Vec<u8>>>>>,
+}
+
+impl Allocate for ReplayAllocator {
+    fn index(&self) -> usize { self.index }
+    fn peers(&self) -> usize { self.peers }
+    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+        let pushers = (0 .. self.peers).map(|_| Box::new(Discard) as Box<dyn Push<Message<T>>>).collect();
+        let puller = ReplayPuller {
+            channel: identifier,
+            pulls: self.pulls.clone(),
+            current: None,
+        };
+        (pushers, Box::new(puller))
+    }
+    fn events(&self) -> &Rc<RefCell<Vec<usize>>> {
+        &self.events
+    }
+}
+
+/// Discards pushed elements.
+struct Discard;
+
+impl<T> Push<T> for Discard {
+    fn push(&mut self, element: &mut Option<T>) {
+        *element = None;
+    }
+}
+
+/// Presents the messages recorded on a channel, up to the step being replayed.
+struct ReplayPuller<T> {
+    channel: usize,
+    pulls: Rc<RefCell<HashMap<usize, VecDeque<Vec<u8>>>>>,
+    current: Option<Message<T>>,
+}
+
+impl<T: Data> Pull<Message<T>> for ReplayPuller<T> {
+    fn pull(&mut self) -> &mut Option<Message<T>> {
+        let message = self.pulls
+            .borrow_mut()
+            .get_mut(&self.channel)
+            .and_then(|queue| queue.pop_front());
+        self.current = message.map(|bytes| unsafe { Message::from_bytes(Bytes::from(bytes)) });
+        &mut self.current
+    }
+}
+
+/// The binary representation of `data`, as it would be sent between workers.
+fn encode<D: Data+Clone>(data: &D) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    Message::from_typed(data.clone()).into_bytes(&mut bytes);
+    bytes
+}
+
+/// Reconstructs data from its binary representation.
+fn decode<D: Data+Clone>(bytes: Vec<u8>) -> D {
+    unsafe { Message::<D>::from_bytes(Bytes::from(bytes)) }.into_typed()
+}
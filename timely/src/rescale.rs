@@ -0,0 +1,262 @@
+//! Rescaling the number of workers of a computation between epochs.
+//!
+//! A rescalable computation runs as a sequence of *generations*, each of which is a
+//! single-process timely computation with a fixed number of workers. Keyed state is
+//! carried from one generation to the next through user code: each worker receives its
+//! share of the state when a generation starts, and returns the state it holds when
+//! the generation ends. Between generations, the state is redistributed among the
+//! workers of the next generation by the hash of its keys, using the same routing as
+//! `Exchange` with the [route] function, and all channels are rebuilt.
+//!
+//! A [RescaleHandle] requests that the computation move to a new number of workers
+//! at an epoch boundary. Workers must call [Generation::try_begin] before they start
+//! each epoch, which reports whether the epoch precedes the boundary of a request. Once
+//! it does not, workers should complete all work for epochs before the boundary, close
+//! their inputs so that their dataflows complete, and return their state. The next
+//! generation resumes from the boundary epoch. Requests for a boundary that some worker
+//! has already begun are rejected, so that no work is done beyond the boundary.
+//!
+//! Rescaling is currently restricted to computations within a single process.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Exchange, Input, Probe};
+//! use timely::rescale::{execute_rescalable, route, RescaleHandle};
+//!
+//! let handle = RescaleHandle::new();
+//! handle.rescale(3, 5);
+//!
+//! // Each key counts the epochs in which it was seen; every key is seen in every epoch.
+//! let initial = (0 .. 10u64).map(|key| (key, 0u64)).collect();
+//! let state = execute_rescalable(2, Default::default(), handle.clone(), initial, |worker, generation| {
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.input_from(&mut input)
+//!              .exchange(|key: &u64| route(key))
+//!              .probe()
+//!     });
+//!
+//!     let mut state = generation.take_state();
+//!     let mut epoch = generation.start();
+//!     input.advance_to(epoch);
+//!     while epoch < 10 && generation.try_begin(epoch) {
+//!         for (_key, count) in state.iter_mut() {
+//!             *count += 1;
+//!         }
+//!         epoch += 1;
+//!         input.advance_to(epoch);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//!     state
+//! }).unwrap();
+//!
+//! assert_eq!(state.len(), 10);
+//! assert!(state.iter().all(|(_key, count)| *count == 10));
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::communication::Allocator;
+use crate::execute::{execute, Config};
+use crate::worker::{Config as WorkerConfig, Worker};
+use crate::CommunicationConfig;
+
+/// Routes `key` to a worker, consistently with the partitioning of rescaled state.
+///
+/// Dataflows that exchange data by key should use this function as their route, so
+/// that data arrive at the worker holding the state for the key.
+pub fn route<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A shared request to rescale a computation.
+#[derive(Clone, Debug, Default)]
+pub struct RescaleHandle {
+    state: Arc<Mutex<HandleState>>,
+}
+
+/// The pending request, and the progress of the current generation.
+#[derive(Debug, Default)]
+struct HandleState {
+    request: Option<(usize, u64)>,
+    /// The first epoch of the current generation.
+    start: u64,
+    /// The greatest epoch any worker of the current generation has begun, if any.
+    begun: Option<u64>,
+}
+
+impl RescaleHandle {
+    /// Creates a new handle, with no pending request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that the computation continue with `workers` workers from `epoch` onward.
+    ///
+    /// Returns `false` without effect if a request is already pending, if `workers` is zero,
+    /// or if `epoch` is not ahead of the current generation: if it is at or before the start
+    /// of the generation, or if some worker has begun it or a later epoch. A request takes
+    /// effect at the end of the current generation, and the handle can be used for another
+    /// request once the next generation has started.
+    pub fn rescale(&self, workers: usize, epoch: u64) -> bool {
+        let mut state = self.state.lock().expect("mutex error?");
+        let ahead = epoch > state.start && state.begun.map(|begun| epoch > begun).unwrap_or(true);
+        if workers == 0 || state.request.is_some() || !ahead {
+            false
+        }
+        else {
+            state.request = Some((workers, epoch));
+            true
+        }
+    }
+
+    /// The pending request, if any, as a number of workers and a boundary epoch.
+    pub fn pending(&self) -> Option<(usize, u64)> {
+        self.state.lock().expect("mutex error?").request
+    }
+
+    /// Records that a worker begins `epoch`, unless it is at or beyond a requested boundary.
+    fn try_begin(&self, epoch: u64) -> bool {
+        let mut state = self.state.lock().expect("mutex error?");
+        if state.request.map(|(_workers, boundary)| epoch >= boundary).unwrap_or(false) {
+            false
+        }
+        else {
+            state.begun = Some(state.begun.map_or(epoch, |begun| std::cmp::max(begun, epoch)));
+            true
+        }
+    }
+
+    /// Takes the pending request, and starts a generation from its boundary if there was one.
+    fn next_generation(&self) -> Option<(usize, u64)> {
+        let mut state = self.state.lock().expect("mutex error?");
+        let request = state.request.take();
+        if let Some((_workers, boundary)) = request {
+            state.start = boundary;
+            state.begun = None;
+        }
+        request
+    }
+}
+
+/// A worker's view of one generation of a rescalable computation.
+pub struct Generation<K, V> {
+    index: usize,
+    start: u64,
+    state: Vec<(K, V)>,
+    handle: RescaleHandle,
+}
+
+impl<K, V> Generation<K, V> {
+    /// The number of generations preceding this one.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The first epoch of this generation; zero for the first generation.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Takes the share of the keyed state assigned to this worker.
+    pub fn take_state(&mut self) -> Vec<(K, V)> {
+        std::mem::take(&mut self.state)
+    }
+
+    /// Indicates whether the worker may begin `epoch`, and records that it has if so.
+    ///
+    /// Workers must call this method before they start each epoch, and must not start the
+    /// epoch if it returns `false`, which happens once `epoch` is at or beyond the boundary of
+    /// a rescaling request. Rescaling requests are only accepted for epochs no worker has
+    /// begun, and so no worker will have done work from the boundary on.
+    pub fn try_begin(&mut self, epoch: u64) -> bool {
+        self.handle.try_begin(epoch)
+    }
+
+    /// The epoch at which this generation ends, if rescaling has been requested.
+    ///
+    /// Once a boundary is reported, it does not change for the rest of the generation.
+    /// Workers should complete all epochs before the boundary, and none from it on. A boundary
+    /// may be requested at any time, and so workers should not rely on this method to decide
+    /// whether to begin an epoch; `try_begin` decides this consistently with the request.
+    pub fn boundary(&self) -> Option<u64> {
+        self.handle.pending().map(|(_workers, epoch)| epoch)
+    }
+}
+
+/// Executes a rescalable computation within a single process.
+///
+/// The computation starts with `workers` workers and the `initial` keyed state. For each
+/// generation, `logic` is invoked by each worker with its share of the state, and should
+/// return the state the worker holds at the end of the generation. When all workers have
+/// returned and their dataflows have completed, a rescaling request pending in `handle`
+/// starts a new generation with the requested number of workers, beginning at the requested
+/// epoch. Without a pending request, the computation ends and the final state is returned.
+///
+/// An error is returned if any worker of any generation fails.
+pub fn execute_rescalable<K, V, F>(
+    workers: usize,
+    worker_config: WorkerConfig,
+    handle: RescaleHandle,
+    initial: Vec<(K, V)>,
+    logic: F,
+) -> Result<Vec<(K, V)>, String>
+where
+    K: Hash+Send+'static,
+    V: Send+'static,
+    F: Fn(&mut Worker<Allocator>, &mut Generation<K, V>)->Vec<(K, V)>+Send+Sync+'static,
+{
+    let logic = Arc::new(logic);
+    let mut workers = workers;
+    let mut start = 0;
+    let mut state = initial;
+
+    for index in 0 .. {
+
+        // Partition the keyed state among the workers of this generation.
+        let mut parts = (0 .. workers).map(|_| Vec::new()).collect::<Vec<_>>();
+        for (key, val) in state.drain(..) {
+            let target = (route(&key) % (workers as u64)) as usize;
+            parts[target].push((key, val));
+        }
+        let parts = Arc::new(Mutex::new(parts));
+
+        let config = Config {
+            communication: CommunicationConfig::Process(workers),
+            worker: worker_config.clone(),
+        };
+        let logic = logic.clone();
+        let generation_handle = handle.clone();
+        let guards = execute(config, move |worker| {
+            let state = std::mem::take(&mut parts.lock().expect("mutex error?")[worker.index()]);
+            let mut generation = Generation {
+                index,
+                start,
+                state,
+                handle: generation_handle.clone(),
+            };
+            (*logic)(worker, &mut generation)
+        })?;
+
+        for result in guards.join() {
+            state.extend(result?);
+        }
+
+        // Clear any request, and continue from it if there was one.
+        match handle.next_generation() {
+            Some((next_workers, boundary)) => {
+                workers = next_workers;
+                start = boundary;
+            }
+            None => break,
+        }
+    }
+
+    Ok(state)
+}
@@ -157,6 +157,51 @@ impl Activations {
             });
     }
 
+    /// Carries the active paths that extend `path` over into the next active set.
+    ///
+    /// This allows a scheduler to defer tasks that it chooses not to schedule in
+    /// the current round, without losing their activations at the next `advance`.
+    pub fn defer(&mut self, path: &[usize]) {
+
+        let position =
+        self.bounds[..self.clean]
+            .binary_search_by_key(&path, |x| &self.slices[x.0 .. (x.0 + x.1)]);
+        let mut position = match position {
+            Ok(x) => x,
+            Err(x) => x,
+        };
+
+        while position < self.clean {
+            let (offset, length) = self.bounds[position];
+            if !self.slices[offset .. (offset + length)].starts_with(path) {
+                break;
+            }
+            self.bounds.push((self.slices.len(), length));
+            self.slices.extend_from_within(offset .. (offset + length));
+            position += 1;
+        }
+    }
+
+    /// The active paths presented by the most recent `advance`.
+    pub fn active_paths(&self) -> Vec<Vec<usize>> {
+        self.bounds[..self.clean]
+            .iter()
+            .map(|(offset, length)| self.slices[*offset .. (*offset + *length)].to_vec())
+            .collect()
+    }
+
+    /// Replaces the active set with `paths`, which must be sorted and deduplicated.
+    ///
+    /// Activations made since the most recent `advance` are discarded.
+    pub fn set_active(&mut self, paths: &[Vec<usize>]) {
+        self.bounds.clear();
+        self.slices.clear();
+        for path in paths.iter() {
+            self.activate(&path[..]);
+        }
+        self.clean = self.bounds.len();
+    }
+
     /// Constructs a thread-safe `SyncActivations` handle to this activator.
     pub fn sync(&self) -> SyncActivations {
         SyncActivations {
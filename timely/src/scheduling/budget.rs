@@ -0,0 +1,76 @@
+//! Time slices for cooperative operator scheduling.
+
+use std::time::{Duration, Instant};
+
+use crate::scheduling::Activator;
+
+/// A budget of time and work for one invocation of operator logic.
+///
+/// Operator logic that may perform unbounded work in response to a single
+/// invocation (e.g. a large batch of input) should check `exhausted()` as it
+/// goes, and once the budget is spent stash any remaining work, call `yield_now()`
+/// to request rescheduling, and return. The budget is replenished at the start
+/// of each invocation.
+///
+/// The budget is described by an optional time slice and an optional amount of
+/// fuel, both set by the worker configuration (see `Config::operator_time_slice`
+/// and `Config::operator_fuel`). A budget with neither is never exhausted.
+#[derive(Debug)]
+pub struct Budget {
+    slice: Option<Duration>,
+    fuel: Option<usize>,
+    start: Instant,
+    remaining: Option<usize>,
+    activator: Activator,
+}
+
+impl Budget {
+    /// Creates a new budget, using `activator` to request rescheduling.
+    pub fn new(slice: Option<Duration>, fuel: Option<usize>, activator: Activator) -> Self {
+        Budget {
+            slice,
+            fuel,
+            start: Instant::now(),
+            remaining: fuel,
+            activator,
+        }
+    }
+
+    /// Replenishes the budget, for a new invocation of operator logic.
+    pub fn reset(&mut self) {
+        self.start = Instant::now();
+        self.remaining = self.fuel;
+    }
+
+    /// Consumes `amount` units of fuel, saturating at zero.
+    ///
+    /// Operators decide what a unit of work is, for example one record.
+    pub fn consume(&mut self, amount: usize) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(amount);
+        }
+    }
+
+    /// The fuel remaining in this invocation, if fuel is bounded.
+    pub fn fuel(&self) -> Option<usize> {
+        self.remaining
+    }
+
+    /// The time remaining in this invocation, if time is bounded.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.slice.map(|slice| slice.checked_sub(self.start.elapsed()).unwrap_or_default())
+    }
+
+    /// True if the operator should stop work and yield.
+    pub fn exhausted(&self) -> bool {
+        self.remaining == Some(0) || self.time_remaining() == Some(Duration::new(0, 0))
+    }
+
+    /// Requests that the operator be scheduled again.
+    ///
+    /// The operator should return promptly after calling this method. It will be
+    /// scheduled again in a subsequent step of the worker, with a fresh budget.
+    pub fn yield_now(&self) {
+        self.activator.activate();
+    }
+}
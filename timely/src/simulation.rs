@@ -0,0 +1,133 @@
+//! Deterministic simulation of multiple workers on a single thread.
+//!
+//! A [Simulation] runs a number of logical workers on the current thread, connected by a
+//! simulated network that holds each message in flight until it is delivered. A scheduler
+//! seeded by the user repeatedly chooses either to step one worker, or to deliver the oldest
+//! message in flight between a pair of workers on some channel. Each seed describes one
+//! interleaving of steps and message deliveries, which is reproduced exactly when the same
+//! seed is used again, so that tests can explore many interleavings and replay any failures.
+//!
+//! Messages between the same pair of workers on the same channel are delivered in the order
+//! they were sent, as with the other allocators. Timed activations still depend on the passage
+//! of real time, and simulated computations that use them may not be reproducible.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+//! use timely::simulation::Simulation;
+//!
+//! for seed in 0 .. 10 {
+//!     let mut simulation = Simulation::new(3, seed, Default::default());
+//!     let (mut inputs, probes): (Vec<_>, Vec<_>) = simulation.workers().iter_mut().map(|worker| {
+//!         let mut input = InputHandle::new();
+//!         let probe = worker.dataflow(|scope| {
+//!             scope.input_from(&mut input)
+//!                  .exchange(|x: &u64| *x)
+//!                  .inspect(|x| println!("seen: {:?}", x))
+//!                  .probe()
+//!         });
+//!         (input, probe)
+//!     }).unzip();
+//!
+//!     for round in 0 .. 5 {
+//!         for (index, input) in inputs.iter_mut().enumerate() {
+//!             input.send(round * 3 + index as u64);
+//!             input.advance_to(round + 1);
+//!         }
+//!         simulation.step_while(|| probes.iter().any(|probe| probe.less_than(&(round + 1))));
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::communication::allocator::Simulated;
+use crate::communication::allocator::simulated::Network;
+use crate::worker::{Config as WorkerConfig, Worker};
+
+/// Workers simulated on a single thread, with a seeded schedule.
+pub struct Simulation {
+    workers: Vec<Worker<Simulated>>,
+    network: Rc<RefCell<Network>>,
+    rng: Rng,
+}
+
+impl Simulation {
+    /// Creates a simulation of `peers` workers, whose schedule is determined by `seed`.
+    pub fn new(peers: usize, seed: u64, config: WorkerConfig) -> Self {
+        let (allocators, network) = Simulated::new_vector(peers);
+        let workers =
+        allocators
+            .into_iter()
+            .map(|allocator| Worker::new(config.clone(), allocator))
+            .collect();
+        Simulation {
+            workers,
+            network,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// The simulated workers, for example to construct dataflows.
+    pub fn workers(&mut self) -> &mut [Worker<Simulated>] {
+        &mut self.workers[..]
+    }
+
+    /// The simulated worker with index `index`.
+    pub fn worker(&mut self, index: usize) -> &mut Worker<Simulated> {
+        &mut self.workers[index]
+    }
+
+    /// Performs one scheduling decision: steps a worker, or delivers a message.
+    ///
+    /// Returns `true` if any worker has dataflows, or if any message is in flight.
+    pub fn step(&mut self) -> bool {
+        let in_flight = self.network.borrow().in_flight();
+        let choice = self.rng.below(self.workers.len() + in_flight.len());
+        if choice < self.workers.len() {
+            self.workers[choice].step();
+        }
+        else {
+            let (channel, source, target) = in_flight[choice - self.workers.len()];
+            self.network.borrow_mut().deliver(channel, source, target);
+        }
+        self.workers.iter().any(|worker| worker.has_dataflows())
+            || !self.network.borrow().in_flight().is_empty()
+    }
+
+    /// Performs scheduling decisions as long as `func` evaluates to true.
+    pub fn step_while<F: FnMut()->bool>(&mut self, mut func: F) {
+        while func() { self.step(); }
+    }
+
+    /// Performs scheduling decisions until no worker has dataflows and no message is in flight.
+    pub fn run(&mut self) {
+        while self.step() { }
+    }
+}
+
+/// A small pseudo-random number generator (SplitMix64), so that schedules depend only on the seed.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0 .. bound`, which must be positive.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % (bound as u64)) as usize
+    }
+}
@@ -0,0 +1,201 @@
+//! Counters describing how a worker spends its time.
+//!
+//! Each worker maintains counters of the time it spends parked, the time it spends in each of its
+//! dataflows and operators, the number of times each operator is scheduled, and the number of
+//! steps in which it scheduled no operators. These are cheaper than the equivalent `ParkEvent`
+//! and `ScheduleEvent` log streams, and a [Utilization] snapshot is available at any moment from
+//! [Worker::utilization](crate::worker::Worker::utilization), for example to export to a metrics
+//! system.
+//!
+//! The time spent in an operator that is a nested scope includes the time spent in the operators
+//! within it. Counters of dataflows and operators are discarded once the dataflow completes or is
+//! dropped; the counters of the worker accumulate for its lifetime.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//!
+//! timely::execute_directly(|worker| {
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.input_from(&mut input)
+//!              .inspect(|x| println!("seen: {:?}", x))
+//!              .probe()
+//!     });
+//!
+//!     for round in 0 .. 10 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(&(round + 1)));
+//!     }
+//!
+//!     let utilization = worker.utilization();
+//!     println!("busy for {:?} of {:?}", utilization.busy(), utilization.elapsed);
+//!     for (address, operator) in utilization.operators.iter() {
+//!         println!("{:?}\t{}\t{:?}", address, operator.name, operator.busy);
+//!     }
+//!     assert!(utilization.dataflows[&0].steps > 0);
+//! });
+//! ```
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+/// A snapshot of the utilization of a worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Utilization {
+    /// The time since the worker was created.
+    pub elapsed: Duration,
+    /// The total time the worker spent parked.
+    pub parked: Duration,
+    /// The number of times the worker parked.
+    pub parks: u64,
+    /// The number of steps the worker performed.
+    pub steps: u64,
+    /// The number of steps in which the worker scheduled no operators.
+    pub idle_steps: u64,
+    /// The total number of times the worker scheduled operators.
+    pub activations: u64,
+    /// Utilization of each installed dataflow, by dataflow index.
+    pub dataflows: BTreeMap<usize, DataflowUtilization>,
+    /// Utilization of each operator of installed dataflows, by operator address.
+    pub operators: BTreeMap<Vec<usize>, OperatorUtilization>,
+}
+
+impl Utilization {
+    /// The total time the worker spent in dataflows.
+    pub fn busy(&self) -> Duration {
+        self.dataflows.values().map(|dataflow| dataflow.busy).sum()
+    }
+}
+
+/// The utilization of one dataflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataflowUtilization {
+    /// The total time the worker spent in the dataflow.
+    pub busy: Duration,
+    /// The number of steps in which the worker scheduled the dataflow.
+    pub steps: u64,
+}
+
+/// The utilization of one operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorUtilization {
+    /// The name of the operator.
+    pub name: String,
+    /// The total time the worker spent in the operator.
+    pub busy: Duration,
+    /// The number of times the worker scheduled the operator.
+    pub activations: u64,
+}
+
+/// Counters of the scheduling of one operator.
+pub(crate) struct OperatorCounter {
+    inner: Rc<OperatorCounterInner>,
+    /// Activations of operators no longer installed, shared with the worker's counters.
+    retired: Rc<Cell<u64>>,
+}
+
+struct OperatorCounterInner {
+    address: Vec<usize>,
+    name: String,
+    busy: Cell<Duration>,
+    activations: Cell<u64>,
+}
+
+impl OperatorCounter {
+    /// Records one scheduling of the operator, which took `elapsed`.
+    #[inline]
+    pub(crate) fn record(&self, elapsed: Duration) {
+        self.inner.busy.set(self.inner.busy.get() + elapsed);
+        self.inner.activations.set(self.inner.activations.get() + 1);
+    }
+}
+
+impl Drop for OperatorCounter {
+    fn drop(&mut self) {
+        self.retired.set(self.retired.get() + self.inner.activations.get());
+    }
+}
+
+/// The utilization counters of a worker.
+#[derive(Default)]
+pub struct Counters {
+    parked: Duration,
+    parks: u64,
+    steps: u64,
+    idle_steps: u64,
+    /// Activations of operators no longer installed.
+    retired: Rc<Cell<u64>>,
+    dataflows: HashMap<usize, DataflowUtilization>,
+    operators: Vec<Weak<OperatorCounterInner>>,
+}
+
+impl Counters {
+    /// Allocates new counters, all zero.
+    pub fn new() -> Self { Self::default() }
+
+    /// Opens counters for the operator at `address`.
+    pub(crate) fn operator(&mut self, address: &[usize], name: &str) -> OperatorCounter {
+        let inner = Rc::new(OperatorCounterInner {
+            address: address.to_vec(),
+            name: name.to_owned(),
+            busy: Cell::new(Duration::default()),
+            activations: Cell::new(0),
+        });
+        self.operators.push(Rc::downgrade(&inner));
+        OperatorCounter { inner, retired: self.retired.clone() }
+    }
+
+    /// Records a step in which the worker parked for `elapsed`, and scheduled no operators.
+    pub(crate) fn park(&mut self, elapsed: Duration) {
+        self.parked += elapsed;
+        self.parks += 1;
+        self.step(true);
+    }
+
+    /// Records a step of the worker, which may have scheduled no operators.
+    pub(crate) fn step(&mut self, idle: bool) {
+        self.steps += 1;
+        if idle { self.idle_steps += 1; }
+    }
+
+    /// Records a step of dataflow `index`, which took `elapsed`.
+    pub(crate) fn step_dataflow(&mut self, index: usize, elapsed: Duration) {
+        let dataflow = self.dataflows.entry(index).or_default();
+        dataflow.busy += elapsed;
+        dataflow.steps += 1;
+    }
+
+    /// Discards the counters of dataflows for which `installed` returns false.
+    pub(crate) fn retain_dataflows<F: FnMut(usize)->bool>(&mut self, mut installed: F) {
+        self.dataflows.retain(|index, _| installed(*index));
+    }
+
+    /// A snapshot of the counters, for a worker that has existed for `elapsed`.
+    pub(crate) fn snapshot(&mut self, elapsed: Duration) -> Utilization {
+        self.operators.retain(|operator| operator.strong_count() > 0);
+        let mut activations = self.retired.get();
+        let mut operators = BTreeMap::new();
+        for operator in self.operators.iter().filter_map(|operator| operator.upgrade()) {
+            activations += operator.activations.get();
+            let entry: &mut OperatorUtilization = operators.entry(operator.address.clone()).or_default();
+            entry.name.clone_from(&operator.name);
+            entry.busy += operator.busy.get();
+            entry.activations += operator.activations.get();
+        }
+        Utilization {
+            elapsed,
+            parked: self.parked,
+            parks: self.parks,
+            steps: self.steps,
+            idle_steps: self.idle_steps,
+            activations,
+            dataflows: self.dataflows.iter().map(|(index, dataflow)| (*index, dataflow.clone())).collect(),
+            operators,
+        }
+    }
+}
@@ -5,18 +5,26 @@ use std::cell::{RefCell, RefMut};
 use std::any::Any;
 use std::str::FromStr;
 use std::time::{Instant, Duration};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::collections::hash_map::Entry;
+use std::cmp::Reverse;
 use std::sync::Arc;
+use std::io::Read;
 
-use crate::communication::{Allocate, Data, Push, Pull};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+
+use crate::communication::{Allocate, CancelHandle, Data, Push, Pull};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 use crate::scheduling::{Schedule, Scheduler, Activations};
 use crate::progress::timestamp::{Refines};
 use crate::progress::SubgraphBuilder;
 use crate::progress::operate::Operate;
 use crate::dataflow::scopes::Child;
-use crate::logging::TimelyLogger;
+use crate::logging::{TimelyLogger, MemoryEvent};
+use crate::record::{Recorder, RecordEvent, RecordPuller};
+use crate::accounting::{Account, AccountKind, Accounting, Usage};
+use crate::utilization::{Counters, Utilization};
+use crate::graph::Graph;
 
 /// Different ways in which timely's progress tracking can work.
 ///
@@ -34,7 +42,8 @@ use crate::logging::TimelyLogger;
 /// If you are not certain which option to use, prefer `Demand`, and
 /// perhaps monitor the progress messages through timely's logging
 /// infrastructure to see if their volume is surprisingly high.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ProgressMode {
     /// Eagerly transmit all progress updates produced by a worker.
     ///
@@ -87,6 +96,22 @@ impl FromStr for ProgressMode {
 pub struct Config {
     /// The progress mode to use.
     pub(crate) progress_mode: ProgressMode,
+    /// Whether operator progress statements should be checked for protocol violations.
+    pub(crate) validate_progress: bool,
+    /// An optional bound on the time spent scheduling dataflows in each step.
+    pub(crate) step_budget: Option<Duration>,
+    /// An optional time slice for each invocation of budgeted operator logic.
+    pub(crate) operator_time_slice: Option<Duration>,
+    /// An optional amount of fuel for each invocation of budgeted operator logic.
+    pub(crate) operator_fuel: Option<usize>,
+    /// An optional address to which `execute` sends timely logging events.
+    pub(crate) timely_log_address: Option<String>,
+    /// An optional address to which `execute` sends communication logging events.
+    pub(crate) communication_log_address: Option<String>,
+    /// An optional interval at which changes in memory usage are logged.
+    pub(crate) memory_log_interval: Option<Duration>,
+    /// Whether records held by output buffers and input handles are accounted.
+    pub(crate) account_buffers: bool,
     /// A map from parameter name to typed parameter values.
     registry: HashMap<String, Arc<dyn Any + Send + Sync>>,
 }
@@ -104,6 +129,12 @@ impl Config {
     #[cfg(feature = "getopts")]
     pub fn install_options(opts: &mut getopts_dep::Options) {
         opts.optopt("", "progress-mode", "progress tracking mode (eager or demand)", "MODE");
+        opts.optflag("", "validate-progress", "check operator progress statements for protocol violations");
+        opts.optopt("", "step-budget", "microseconds spent scheduling dataflows in each step", "MICROS");
+        opts.optopt("", "operator-time-slice", "microseconds budgeted operators run before yielding", "MICROS");
+        opts.optopt("", "operator-fuel", "units of work budgeted operators perform before yielding", "FUEL");
+        opts.optopt("", "memory-log-interval", "microseconds between logs of changes in memory usage", "MICROS");
+        opts.optflag("", "account-buffers", "account records held by output buffers and input handles");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -118,7 +149,31 @@ impl Config {
     pub fn from_matches(matches: &getopts_dep::Matches) -> Result<Config, String> {
         let progress_mode = matches
             .opt_get_default("progress-mode", ProgressMode::Eager)?;
-        Ok(Config::default().progress_mode(progress_mode))
+        let validate_progress = matches.opt_present("validate-progress");
+        let step_budget = matches
+            .opt_get::<u64>("step-budget")
+            .map_err(|e| format!("invalid step budget: {}", e))?
+            .map(Duration::from_micros);
+        let operator_time_slice = matches
+            .opt_get::<u64>("operator-time-slice")
+            .map_err(|e| format!("invalid operator time slice: {}", e))?
+            .map(Duration::from_micros);
+        let operator_fuel = matches
+            .opt_get::<usize>("operator-fuel")
+            .map_err(|e| format!("invalid operator fuel: {}", e))?;
+        let memory_log_interval = matches
+            .opt_get::<u64>("memory-log-interval")
+            .map_err(|e| format!("invalid memory log interval: {}", e))?
+            .map(Duration::from_micros);
+        let account_buffers = matches.opt_present("account-buffers");
+        Ok(Config::default()
+            .progress_mode(progress_mode)
+            .validate_progress(validate_progress)
+            .step_budget(step_budget)
+            .operator_time_slice(operator_time_slice)
+            .operator_fuel(operator_fuel)
+            .memory_log_interval(memory_log_interval)
+            .account_buffers(account_buffers))
     }
 
     /// Sets the progress mode to `progress_mode`.
@@ -127,6 +182,96 @@ impl Config {
         self
     }
 
+    /// Enables or disables checked progress tracking.
+    ///
+    /// When enabled, each scope validates the progress statements of its operators
+    /// as they are reported, and panics on the first violation of the progress
+    /// protocol, naming the offending operator and its address. Violations include
+    /// negative capability counts, messages produced at times the operator could not
+    /// have held a capability for, and messages consumed that were never produced.
+    ///
+    /// The checks have a cost proportional to the volume of progress updates, and
+    /// are meant for debugging custom operators rather than for production use.
+    pub fn validate_progress(mut self, validate: bool) -> Self {
+        self.validate_progress = validate;
+        self
+    }
+
+    /// Bounds the time spent scheduling dataflows in each step.
+    ///
+    /// Each step schedules active dataflows in order of their priority (see
+    /// [Worker::dataflow_core_prioritized]). Once the time spent in a step exceeds
+    /// `budget`, the remaining active dataflows are deferred to the next step, where
+    /// they are scheduled ahead of all others and regardless of the budget. At least one
+    /// dataflow is scheduled in each step, and a dataflow is never deferred for more than
+    /// one step in a row.
+    ///
+    /// With no budget, which is the default, all active dataflows are scheduled in
+    /// each step, still in order of their priority.
+    pub fn step_budget(mut self, budget: Option<Duration>) -> Self {
+        self.step_budget = budget;
+        self
+    }
+
+    /// Sets the time slice for each invocation of budgeted operator logic.
+    ///
+    /// Operators built with `build_budgeted` receive a [Budget](crate::scheduling::Budget)
+    /// which is exhausted once the logic has run for `slice`, at which point the logic
+    /// should yield. With no time slice, which is the default, time is unbounded.
+    pub fn operator_time_slice(mut self, slice: Option<Duration>) -> Self {
+        self.operator_time_slice = slice;
+        self
+    }
+
+    /// Sets the fuel for each invocation of budgeted operator logic.
+    ///
+    /// Operators built with `build_budgeted` receive a [Budget](crate::scheduling::Budget)
+    /// which is exhausted once the logic has consumed `fuel` units of work, at which point
+    /// the logic should yield. With no fuel, which is the default, work is unbounded.
+    pub fn operator_fuel(mut self, fuel: Option<usize>) -> Self {
+        self.operator_fuel = fuel;
+        self
+    }
+
+    /// Sets the address to which `execute` sends timely logging events from each worker.
+    ///
+    /// With no address, which is the default, `execute` uses the address in the
+    /// `TIMELY_WORKER_LOG_ADDR` environment variable, if it is set.
+    pub fn timely_log_address(mut self, address: Option<String>) -> Self {
+        self.timely_log_address = address;
+        self
+    }
+
+    /// Sets the address to which `execute` sends communication logging events.
+    ///
+    /// With no address, which is the default, `execute` uses the address in the
+    /// `TIMELY_COMM_LOG_ADDR` environment variable, if it is set.
+    pub fn communication_log_address(mut self, address: Option<String>) -> Self {
+        self.communication_log_address = address;
+        self
+    }
+
+    /// Sets the interval at which changes in memory usage are logged.
+    ///
+    /// At most once per `interval`, the worker logs a `MemoryEvent` to the "timely" log stream
+    /// for each operator component whose memory usage changed since it was last logged (see
+    /// [crate::accounting]). With no interval, which is the default, memory usage is not logged.
+    pub fn memory_log_interval(mut self, interval: Option<Duration>) -> Self {
+        self.memory_log_interval = interval;
+        self
+    }
+
+    /// Sets whether records held by output buffers and input handles are accounted.
+    ///
+    /// Output buffers and input handles update their account for each record given to them,
+    /// which is a cost on the paths along which records are produced. They are not accounted by
+    /// default, and their usage is then not reported nor logged. Channels and operator state
+    /// are accounted regardless, as they update their accounts once per container.
+    pub fn account_buffers(mut self, account: bool) -> Self {
+        self.account_buffers = account;
+        self
+    }
+
     /// Sets a typed configuration parameter for the given `key`.
     ///
     /// It is recommended to install a single configuration struct using a key
@@ -204,6 +349,25 @@ pub trait AsWorker : Scheduler {
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>>;
     /// Provides access to the timely logging stream.
     fn logging(&self) -> Option<crate::logging::TimelyLogger> { self.log_register().get("timely") }
+    /// Provides a shared handle to the accounts of memory held by dataflows.
+    ///
+    /// The default implementation returns new accounts on each call, which are not reported
+    /// by the worker. Implementors should return accounts shared with their worker.
+    fn accounting(&self) -> Rc<RefCell<Accounting>> { Rc::new(RefCell::new(Accounting::new())) }
+    /// Opens an account for state held by the operator at the specified address.
+    fn account_for(&self, path: &[usize]) -> Account {
+        self.accounting().borrow_mut().account(path, AccountKind::State)
+    }
+    /// Provides a shared handle to the utilization counters of the worker.
+    ///
+    /// The default implementation returns new counters on each call, which are not reported
+    /// by the worker. Implementors should return counters shared with their worker.
+    fn utilization_counters(&self) -> Rc<RefCell<Counters>> { Rc::new(RefCell::new(Counters::new())) }
+    /// Provides a shared handle to the structure of the worker's dataflows.
+    ///
+    /// The default implementation returns a new graph on each call, which is not reported
+    /// by the worker. Implementors should return a graph shared with their worker.
+    fn graph(&self) -> Rc<RefCell<Graph>> { Rc::new(RefCell::new(Graph::new())) }
 }
 
 /// A `Worker` is the entry point to a timely dataflow computation. It wraps a `Allocate`,
@@ -225,6 +389,91 @@ pub struct Worker<A: Allocate> {
     // Temporary storage for channel identifiers during dataflow construction.
     // These are then associated with a dataflow once constructed.
     temp_channel_ids: Rc<RefCell<Vec<usize>>>,
+
+    // Worker-to-worker coordination of dropped dataflows and cancellation.
+    control: Rc<RefCell<Control>>,
+
+    // Records the execution of the worker, if requested.
+    recorder: Option<Recorder>,
+
+    // Periodic activations of operators.
+    timers: Rc<RefCell<Vec<Timer>>>,
+
+    // Accounts of memory held by dataflows.
+    accounting: Rc<RefCell<Accounting>>,
+    // The next time memory usage should be logged, relative to the worker's timer.
+    memory_log_next: Duration,
+
+    // Counters of how the worker spends its time.
+    counters: Rc<RefCell<Counters>>,
+
+    // Operators and channels of installed dataflows.
+    graph: Rc<RefCell<Graph>>,
+}
+
+/// A periodic activation of the operator at `path`.
+struct Timer {
+    path: Vec<usize>,
+    period: Duration,
+    /// The next activation, relative to the worker's timer.
+    next: Duration,
+}
+
+/// Messages forwarded by a thread on behalf of an external source, with `activate_on_receiver`
+/// or `activate_on_reader`.
+///
+/// The messages are received through the `Receiver` this dereferences to. Dropping the
+/// `ExternalReceiver`, for example along with the operator holding it, stops the thread.
+pub struct ExternalReceiver<T> {
+    receiver: Receiver<T>,
+    /// Disconnects as the receiver is dropped, interrupting the thread.
+    _shutdown: Sender<()>,
+}
+
+impl<T> std::ops::Deref for ExternalReceiver<T> {
+    type Target = Receiver<T>;
+    fn deref(&self) -> &Receiver<T> { &self.receiver }
+}
+
+/// The identifier of the channel workers use to coordinate with each other.
+///
+/// The channel is allocated as each worker is constructed, before any dataflow
+/// channels, so that all workers agree on its identifier.
+const CONTROL_CHANNEL: usize = 0;
+
+/// Requests exchanged among workers on the control channel.
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone)]
+enum ControlMessage {
+    /// Drop the dataflow with the given index.
+    DropDataflow(usize),
+    /// Drop all dataflows, including those constructed in the future.
+    Cancel,
+}
+
+/// State for coordinating workers.
+struct Control {
+    /// Send endpoints to each worker.
+    pushers: Vec<Box<dyn Push<Message<ControlMessage>>>>,
+    /// Receive endpoint for requests from other workers.
+    puller: Box<dyn Pull<Message<ControlMessage>>>,
+    /// Indices of dataflows dropped before this worker constructed them.
+    pending: HashSet<usize>,
+    /// A shared cancellation request, for example from the launching thread.
+    cancel: Option<CancelHandle>,
+    /// Set once this worker has been cancelled.
+    cancelled: bool,
+}
+
+impl Control {
+    /// Sends `message` to all workers other than `index`.
+    fn broadcast(&mut self, index: usize, message: ControlMessage) {
+        for (peer, pusher) in self.pushers.iter_mut().enumerate() {
+            if peer != index {
+                pusher.send(Message::from_typed(message.clone()));
+                pusher.done();
+            }
+        }
+    }
 }
 
 impl<A: Allocate> AsWorker for Worker<A> {
@@ -236,7 +485,11 @@ impl<A: Allocate> AsWorker for Worker<A> {
         let mut paths = self.paths.borrow_mut();
         paths.insert(identifier, address.to_vec());
         self.temp_channel_ids.borrow_mut().push(identifier);
-        self.allocator.borrow_mut().allocate(identifier)
+        let (pushers, puller) = self.allocator.borrow_mut().allocate(identifier);
+        match &self.recorder {
+            Some(recorder) => (pushers, Box::new(RecordPuller::new(identifier, puller, recorder.clone()))),
+            None => (pushers, puller),
+        }
     }
     fn pipeline<T: 'static>(&mut self, identifier: usize, address: &[usize]) -> (ThreadPusher<Message<T>>, ThreadPuller<Message<T>>) {
         if address.is_empty() { panic!("Unacceptable address: Length zero"); }
@@ -250,6 +503,9 @@ impl<A: Allocate> AsWorker for Worker<A> {
     fn log_register(&self) -> RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.log_register()
     }
+    fn accounting(&self) -> Rc<RefCell<Accounting>> { self.accounting.clone() }
+    fn utilization_counters(&self) -> Rc<RefCell<Counters>> { self.counters.clone() }
+    fn graph(&self) -> Rc<RefCell<Graph>> { self.graph.clone() }
 }
 
 impl<A: Allocate> Scheduler for Worker<A> {
@@ -260,21 +516,36 @@ impl<A: Allocate> Scheduler for Worker<A> {
 
 impl<A: Allocate> Worker<A> {
     /// Allocates a new `Worker` bound to a channel allocator.
-    pub fn new(config: Config, c: A) -> Worker<A> {
+    pub fn new(config: Config, mut c: A) -> Worker<A> {
         let now = Instant::now();
         let index = c.index();
+        let (pushers, puller) = c.allocate(CONTROL_CHANNEL);
+        let control = Control {
+            pushers,
+            puller,
+            pending: HashSet::new(),
+            cancel: None,
+            cancelled: false,
+        };
         Worker {
             config,
             timer: now,
             paths:  Default::default(),
             allocator: Rc::new(RefCell::new(c)),
-            identifiers:  Default::default(),
+            identifiers: Rc::new(RefCell::new(CONTROL_CHANNEL + 1)),
             dataflows: Default::default(),
             dataflow_counter:  Default::default(),
             logging: Rc::new(RefCell::new(crate::logging_core::Registry::new(now, index))),
             activations: Rc::new(RefCell::new(Activations::new(now))),
             active_dataflows: Default::default(),
             temp_channel_ids:  Default::default(),
+            control: Rc::new(RefCell::new(control)),
+            recorder: None,
+            timers: Rc::new(RefCell::new(Vec::new())),
+            accounting: Rc::new(RefCell::new(Accounting::new())),
+            memory_log_next: Duration::default(),
+            counters: Rc::new(RefCell::new(Counters::new())),
+            graph: Rc::new(RefCell::new(Graph::new())),
         }
     }
 
@@ -354,13 +625,39 @@ impl<A: Allocate> Worker<A> {
             }
         }
 
+        // Respond to requests from other workers, and to cancellation.
+        let (requests, cancel) = {
+            let mut control = self.control.borrow_mut();
+            let mut requests = Vec::new();
+            while let Some(message) = control.puller.recv() {
+                requests.push((*message).clone());
+            }
+            let cancel = !control.cancelled && control.cancel.as_ref().map(|c| c.is_cancelled()).unwrap_or(false);
+            (requests, cancel)
+        };
+        for request in requests {
+            match request {
+                ControlMessage::DropDataflow(index) => self.drop_dataflow_local(index),
+                ControlMessage::Cancel => self.cancel_local(),
+            }
+        }
+        if cancel {
+            self.cancel();
+        }
+
+        // Activate operators with due timers.
+        let next_timer = self.fire_timers();
+
         // Organize activations.
         self.activations
             .borrow_mut()
             .advance();
 
         // Consider parking only if we have no pending events, some dataflows, and a non-zero duration.
-        let empty_for = self.activations.borrow().empty_for();
+        let empty_for = match (self.activations.borrow().empty_for(), next_timer) {
+            (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
+            (x, y) => x.or(y),
+        };
         // Determine the minimum park duration, where `None` are an absence of a constraint.
         let delay = match (duration, empty_for) {
             (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
@@ -375,9 +672,15 @@ impl<A: Allocate> Worker<A> {
                 l.flush();
             }
 
-            self.allocator
-                .borrow()
-                .await_events(delay);
+            let parked = Instant::now();
+            {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::trace_span!("park", worker = self.index(), delay = ?delay).entered();
+                self.allocator
+                    .borrow()
+                    .await_events(delay);
+            }
+            self.counters.borrow_mut().park(parked.elapsed());
 
             // Log return from unpark.
             self.logging().as_mut().map(|l| l.log(crate::logging::ParkEvent::unpark()));
@@ -390,28 +693,179 @@ impl<A: Allocate> Worker<A> {
                 .for_extensions(&[], |index| active_dataflows.push(index));
 
             let mut dataflows = self.dataflows.borrow_mut();
-            for index in active_dataflows.drain(..) {
-                // Step dataflow if it exists, remove if not incomplete.
-                if let Entry::Occupied(mut entry) = dataflows.entry(index) {
-                    // TODO: This is a moment at which a scheduling decision is being made.
-                    let incomplete = entry.get_mut().step();
-                    if !incomplete {
-                        let mut paths = self.paths.borrow_mut();
-                        for channel in entry.get_mut().channel_ids.drain(..) {
-                            paths.remove(&channel);
-                        }
-                        entry.remove_entry();
+
+            // Schedule deferred dataflows first, then by decreasing priority.
+            // The sort is stable, so ties retain their activation order.
+            active_dataflows.sort_by_key(|index| {
+                dataflows
+                    .get(index)
+                    .map(|wrapper| (Reverse(wrapper.deferred), Reverse(wrapper.priority)))
+            });
+
+            // Record the activations that determine this step.
+            let active = self.recorder.as_ref().map(|_| self.activations.borrow().active_paths());
+            let mut stepped = Vec::new();
+
+            let mut idle = true;
+
+            let start = Instant::now();
+            let mut deferred = Vec::new();
+            let mut drain = active_dataflows.drain(..);
+            for index in drain.by_ref() {
+                if let Some(wrapper) = dataflows.get(&index) {
+                    // Dataflows deferred by the previous step are scheduled regardless of the budget.
+                    let exhausted = !idle && self.config.step_budget.map(|budget| start.elapsed() >= budget).unwrap_or(false);
+                    if exhausted && !wrapper.deferred {
+                        deferred.push(index);
+                        break;
                     }
+                    if active.is_some() { stepped.push(index); }
+                    Self::step_dataflow(&mut dataflows, &self.paths, &self.counters, &self.graph, index);
+                    idle = false;
                 }
             }
+            deferred.extend(drain);
+            self.counters.borrow_mut().step(idle);
+
+            // Defer the remaining dataflows to the next step.
+            let mut activations = self.activations.borrow_mut();
+            for index in deferred {
+                if let Some(wrapper) = dataflows.get_mut(&index) {
+                    wrapper.deferred = true;
+                    activations.defer(&[index]);
+                }
+            }
+
+            if let (Some(recorder), Some(active)) = (&self.recorder, active) {
+                recorder.record(&RecordEvent::Step { active, stepped });
+            }
         }
 
+        self.log_memory();
+
         // Clean up, indicate if dataflows remain.
         self.logging.borrow_mut().flush();
         self.allocator.borrow_mut().release();
         !self.dataflows.borrow().is_empty()
     }
 
+    /// Logs changes in memory usage, once the configured interval has passed.
+    fn log_memory(&mut self) {
+        if let Some(interval) = self.config.memory_log_interval {
+            let now = self.timer.elapsed();
+            if self.memory_log_next <= now {
+                self.memory_log_next = now + interval;
+                if let Some(logger) = self.logging() {
+                    for ((addr, kind), usage) in self.accounting.borrow_mut().changes() {
+                        logger.log(MemoryEvent { addr, kind, records: usage.records, bytes: usage.bytes });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Activates operators whose timers are due, and reports the time until the next timer.
+    ///
+    /// Timers of dataflows that no longer exist are discarded.
+    fn fire_timers(&mut self) -> Option<Duration> {
+        let mut timers = self.timers.borrow_mut();
+        if timers.is_empty() { return None; }
+        let dataflows = self.dataflows.borrow();
+        timers.retain(|timer| dataflows.contains_key(&timer.path[0]));
+        let now = self.timer.elapsed();
+        let mut activations = self.activations.borrow_mut();
+        let mut next_timer = None;
+        for timer in timers.iter_mut() {
+            if timer.next <= now {
+                activations.activate(&timer.path[..]);
+                // Skip any activations missed while the worker was busy.
+                while timer.next <= now { timer.next += timer.period; }
+            }
+            let until = timer.next - now;
+            next_timer = Some(next_timer.map_or(until, |next: Duration| std::cmp::min(next, until)));
+        }
+        next_timer
+    }
+
+    /// Steps dataflow `index`, and removes it if it is complete.
+    ///
+    /// The counters are borrowed only after the step, as operators may read the worker's utilization.
+    fn step_dataflow(dataflows: &mut HashMap<usize, Wrapper>, paths: &RefCell<HashMap<usize, Vec<usize>>>, counters: &RefCell<Counters>, graph: &RefCell<Graph>, index: usize) {
+        if let Entry::Occupied(mut entry) = dataflows.entry(index) {
+            entry.get_mut().deferred = false;
+            let start = Instant::now();
+            let incomplete = entry.get_mut().step();
+            let elapsed = start.elapsed();
+            if incomplete {
+                counters.borrow_mut().step_dataflow(index, elapsed);
+            }
+            else {
+                let mut paths = paths.borrow_mut();
+                for channel in entry.get_mut().channel_ids.drain(..) {
+                    paths.remove(&channel);
+                }
+                entry.remove_entry();
+                counters.borrow_mut().retain_dataflows(|dataflow| dataflow != index);
+                graph.borrow_mut().retain_dataflows(|dataflow| dataflow != index);
+            }
+        }
+    }
+
+    /// Records the execution of the worker to `writer`, for replay with `record::Replay`.
+    ///
+    /// Only channels allocated after this call are recorded, and the method should be called
+    /// before any dataflows are constructed. Input handles are recorded once registered with
+    /// the returned recorder.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///     let recording = std::env::temp_dir().join(format!("worker-{}.recording", worker.index()));
+    ///     let _recorder = worker.record(std::fs::File::create(recording).unwrap());
+    /// });
+    /// ```
+    pub fn record<W: std::io::Write+'static>(&mut self, writer: W) -> Recorder {
+        let recorder = Recorder::new(writer, self.index(), self.peers());
+        self.recorder = Some(recorder.clone());
+        recorder
+    }
+
+    /// Performs a recorded step, with the recorded activations and order of dataflows.
+    ///
+    /// Channel events and other activations are discarded, as they are reflected in the
+    /// recorded activations.
+    pub(crate) fn step_recorded(&mut self, active: &[Vec<usize>], stepped: &[usize]) -> bool {
+
+        self.allocator.borrow().events().borrow_mut().clear();
+        {
+            let mut activations = self.activations.borrow_mut();
+            activations.advance();
+            activations.set_active(active);
+        }
+
+        let mut dataflows = self.dataflows.borrow_mut();
+        for index in stepped.iter() {
+            Self::step_dataflow(&mut dataflows, &self.paths, &self.counters, &self.graph, *index);
+        }
+
+        // Defer the remaining active dataflows to the next step.
+        let mut activations = self.activations.borrow_mut();
+        let mut deferred = Vec::new();
+        activations.for_extensions(&[], |index| if !stepped.contains(&index) { deferred.push(index); });
+        for index in deferred {
+            if let Some(wrapper) = dataflows.get_mut(&index) {
+                wrapper.deferred = true;
+                activations.defer(&[index]);
+            }
+        }
+        drop(activations);
+        drop(dataflows);
+
+        self.logging.borrow_mut().flush();
+        self.allocator.borrow_mut().release();
+        !self.dataflows.borrow().is_empty()
+    }
+
     /// Calls `self.step()` as long as `func` evaluates to true.
     ///
     /// This method will continually execute even if there is not work
@@ -567,6 +1021,35 @@ impl<A: Allocate> Worker<A> {
         self.dataflow_core("Dataflow", logging, Box::new(()), |_, child| func(child))
     }
 
+    /// Construct a new dataflow, reporting an error if it cannot be built.
+    ///
+    /// This method behaves as `dataflow`, but rather than panic if the dataflow contains a
+    /// cycle along which timestamps do not strictly advance, it returns a description of the
+    /// operators and channels in each such cycle. The dataflow is not installed in that case.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Concat, ConnectLoop, Feedback, ToStream};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let result = worker.try_dataflow::<u64,_,_>(|scope| {
+    ///         let (handle, cycle) = scope.feedback::<Vec<u64>>(Default::default());
+    ///         (0 .. 10).to_stream(scope)
+    ///                  .concat(&cycle)
+    ///                  .connect_loop(handle);
+    ///     });
+    ///     assert!(result.is_err());
+    /// });
+    /// ```
+    pub fn try_dataflow<T, R, F>(&mut self, func: F) -> Result<R, String>
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut Child<Self, T>)->R,
+    {
+        let logging = self.logging.borrow_mut().get("timely");
+        self.try_dataflow_core_prioritized("Dataflow", logging, Box::new(()), 0, |_, child| func(child))
+    }
+
     /// Construct a new dataflow with a (purely cosmetic) name.
     ///
     /// # Examples
@@ -616,7 +1099,53 @@ impl<A: Allocate> Worker<A> {
     ///     );
     /// });
     /// ```
-    pub fn dataflow_core<T, R, F, V>(&mut self, name: &str, mut logging: Option<TimelyLogger>, mut resources: V, func: F) -> R
+    pub fn dataflow_core<T, R, F, V>(&mut self, name: &str, logging: Option<TimelyLogger>, resources: V, func: F) -> R
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut V, &mut Child<Self, T>)->R,
+        V: Any+'static,
+    {
+        self.dataflow_core_prioritized(name, logging, resources, 0, func)
+    }
+
+    /// Construct a new dataflow with specific configurations and a scheduling priority.
+    ///
+    /// This method behaves as `dataflow_core`, and additionally sets the priority of
+    /// the dataflow. In each step, active dataflows with greater priority are scheduled
+    /// before those with lesser priority; dataflows constructed by other methods have
+    /// priority zero. In combination with [Config::step_budget], this allows
+    /// latency-sensitive dataflows to be scheduled promptly alongside heavier ones.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     // A dataflow scheduled ahead of those with default priority.
+    ///     worker.dataflow_core_prioritized::<usize,_,_,_>(
+    ///         "urgent dataflow",
+    ///         None,
+    ///         (),
+    ///         10,
+    ///         |_resources, scope| {
+    ///
+    ///             // uses of `scope` to build dataflow
+    ///
+    ///         }
+    ///     );
+    /// });
+    /// ```
+    pub fn dataflow_core_prioritized<T, R, F, V>(&mut self, name: &str, logging: Option<TimelyLogger>, resources: V, priority: i64, func: F) -> R
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut V, &mut Child<Self, T>)->R,
+        V: Any+'static,
+    {
+        self.try_dataflow_core_prioritized(name, logging, resources, priority, func)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Construct a new dataflow as `dataflow_core_prioritized`, reporting an error if it cannot be built.
+    fn try_dataflow_core_prioritized<T, R, F, V>(&mut self, name: &str, mut logging: Option<TimelyLogger>, mut resources: V, priority: i64, func: F) -> Result<R, String>
     where
         T: Refines<()>,
         F: FnOnce(&mut V, &mut Child<Self, T>)->R,
@@ -640,7 +1169,20 @@ impl<A: Allocate> Worker<A> {
             func(&mut resources, &mut builder)
         };
 
-        let mut operator = subscope.into_inner().build(self);
+        let mut operator = match subscope.into_inner().try_build(self) {
+            Ok(operator) => operator,
+            Err(error) => {
+                // Forget the channels and operators of the dataflow.
+                let mut paths = self.paths.borrow_mut();
+                for channel in self.temp_channel_ids.borrow_mut().drain(..) {
+                    paths.remove(&channel);
+                }
+                self.graph.borrow_mut().retain_dataflows(|index| index != dataflow_index);
+                return Err(error);
+            },
+        };
+
+        self.graph.borrow_mut().add_operator(identifier, operator.path(), operator.name(), operator.inputs(), operator.outputs());
 
         if let Some(l) = logging.as_mut() {
             l.log(crate::logging::OperatesEvent {
@@ -657,35 +1199,152 @@ impl<A: Allocate> Worker<A> {
         let mut temp_channel_ids = self.temp_channel_ids.borrow_mut();
         let channel_ids = temp_channel_ids.drain(..).collect::<Vec<_>>();
 
-        let wrapper = Wrapper {
+        let mut wrapper = Wrapper {
             logging,
             identifier,
             operate: Some(Box::new(operator)),
             resources: Some(Box::new(resources)),
             channel_ids,
+            priority,
+            deferred: false,
+        };
+
+        // Another worker may have dropped the dataflow before we constructed it.
+        let dropped = {
+            let mut control = self.control.borrow_mut();
+            control.pending.remove(&dataflow_index) || control.cancelled
         };
-        self.dataflows.borrow_mut().insert(dataflow_index, wrapper);
+        if dropped {
+            let mut paths = self.paths.borrow_mut();
+            for channel in wrapper.channel_ids.drain(..) {
+                paths.remove(&channel);
+            }
+            self.graph.borrow_mut().retain_dataflows(|index| index != dataflow_index);
+        }
+        else {
+            self.dataflows.borrow_mut().insert(dataflow_index, wrapper);
+        }
 
-        result
+        Ok(result)
 
     }
 
     /// Drops an identified dataflow.
     ///
-    /// This method removes the identified dataflow, which will no longer be scheduled.
-    /// Various other resources will be cleaned up, though the method is currently in
-    /// public beta rather than expected to work. Please report all crashes and unmet
-    /// expectations!
+    /// This method removes the identified dataflow, which will no longer be scheduled,
+    /// and asks all other workers to do the same. Other workers drop the dataflow the
+    /// next time they step, or as soon as they construct it if they have not yet done
+    /// so. Dropping a dataflow that has already completed or been dropped has no effect.
+    ///
+    /// Messages in flight on the dataflow's channels are not delivered, as each worker
+    /// drops its channel endpoints along with the dataflow.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    ///     let index = worker.next_dataflow_index();
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 1000)
+    ///             .to_stream(scope)
+    ///             .exchange(|x| *x as u64)
+    ///             .inspect(|_x| { });
+    ///     });
+    ///
+    ///     // Only the first worker needs to request the drop.
+    ///     if worker.index() == 0 {
+    ///         worker.drop_dataflow(index);
+    ///     }
+    ///     while worker.has_dataflows() {
+    ///         worker.step();
+    ///     }
+    /// });
+    /// ```
     pub fn drop_dataflow(&mut self, dataflow_identifier: usize) {
-        if let Some(mut entry) = self.dataflows.borrow_mut().remove(&dataflow_identifier) {
+        self.drop_dataflow_local(dataflow_identifier);
+        let index = self.index();
+        self.control
+            .borrow_mut()
+            .broadcast(index, ControlMessage::DropDataflow(dataflow_identifier));
+    }
+
+    /// Drops an identified dataflow at this worker only.
+    ///
+    /// Dataflows that have not yet been constructed are recorded, and dropped as
+    /// soon as they are constructed.
+    fn drop_dataflow_local(&mut self, dataflow_identifier: usize) {
+        if dataflow_identifier >= self.next_dataflow_index() {
+            self.control.borrow_mut().pending.insert(dataflow_identifier);
+        }
+        // Bind the removed dataflow, so that it is dropped after the borrow is released.
+        let removed = self.dataflows.borrow_mut().remove(&dataflow_identifier);
+        if let Some(mut entry) = removed {
             // Garbage collect channel_id to path information.
             let mut paths = self.paths.borrow_mut();
             for channel in entry.channel_ids.drain(..) {
                 paths.remove(&channel);
             }
+            self.counters.borrow_mut().retain_dataflows(|index| index != dataflow_identifier);
+            self.graph.borrow_mut().retain_dataflows(|index| index != dataflow_identifier);
+        }
+    }
+
+    /// Cancels the computation at all workers.
+    ///
+    /// This method drops all installed dataflows, and asks all other workers to do
+    /// the same. Dataflows constructed by any worker after it has been cancelled are
+    /// dropped immediately, so that loops of the form `while worker.has_dataflows()`
+    /// terminate promptly. Loops that instead await a probe or other condition should
+    /// also consult `is_cancelled()`, as dropped dataflows no longer make progress.
+    ///
+    /// Cancellation can also be requested from outside the workers, using the handle
+    /// returned by `WorkerGuards::cancel_handle` for computations started with `execute`.
+    pub fn cancel(&mut self) {
+        if !self.is_cancelled() {
+            self.cancel_local();
+            let index = self.index();
+            self.control
+                .borrow_mut()
+                .broadcast(index, ControlMessage::Cancel);
+        }
+    }
+
+    /// True if the computation has been cancelled at this worker.
+    pub fn is_cancelled(&self) -> bool {
+        self.control.borrow().cancelled
+    }
+
+    /// Associates a shared cancellation request with the worker.
+    ///
+    /// The worker checks the handle each time it steps, and cancels the computation
+    /// once the handle is cancelled. This method is used by `execute` and related
+    /// methods, and need not be called otherwise.
+    pub fn set_cancel_handle(&mut self, handle: CancelHandle) {
+        self.control.borrow_mut().cancel = Some(handle);
+    }
+
+    /// Cancels the computation at this worker only.
+    fn cancel_local(&mut self) {
+        self.control.borrow_mut().cancelled = true;
+        for dataflow_index in self.installed_dataflows() {
+            self.drop_dataflow_local(dataflow_index);
         }
     }
 
+    /// Sets the scheduling priority of an identified dataflow.
+    ///
+    /// Returns `false` if the dataflow is not installed. See
+    /// [Worker::dataflow_core_prioritized] for the meaning of priorities.
+    pub fn set_dataflow_priority(&mut self, dataflow_identifier: usize, priority: i64) -> bool {
+        self.dataflows
+            .borrow_mut()
+            .get_mut(&dataflow_identifier)
+            .map(|wrapper| wrapper.priority = priority)
+            .is_some()
+    }
+
     /// Returns the next index to be used for dataflow construction.
     ///
     /// This identifier will appear in the address of contained operators, and can
@@ -699,11 +1358,151 @@ impl<A: Allocate> Worker<A> {
         self.dataflows.borrow().keys().cloned().collect()
     }
 
+    /// The memory held by each operator, by operator address.
+    ///
+    /// Operators without open accounts are not listed. See [crate::accounting] for what is accounted.
+    pub fn memory_usage(&self) -> BTreeMap<Vec<usize>, Usage> {
+        self.accounting.borrow_mut().by_address()
+    }
+
+    /// The memory held by each dataflow, by dataflow index.
+    ///
+    /// Dataflows without open accounts are not listed. See [crate::accounting] for what is accounted.
+    pub fn dataflow_memory_usage(&self) -> BTreeMap<usize, Usage> {
+        self.accounting.borrow_mut().by_dataflow()
+    }
+
+    /// A snapshot of the utilization of the worker.
+    ///
+    /// See [crate::utilization] for what the snapshot reports.
+    pub fn utilization(&self) -> Utilization {
+        self.counters.borrow_mut().snapshot(self.timer.elapsed())
+    }
+
+    /// The operators and channels of installed dataflows.
+    ///
+    /// See [crate::graph] for what the graph describes.
+    pub fn dataflow_graph(&self) -> Graph {
+        self.graph.borrow().clone()
+    }
+
     /// True if there is at least one dataflow under management.
     pub fn has_dataflows(&self) -> bool {
         !self.dataflows.borrow().is_empty()
     }
 
+    /// Steps the worker until it has no dataflows, parking it while it has no work to do.
+    ///
+    /// The worker is unparked by activations of its operators, including those of external
+    /// sources registered with `activate_on_receiver`, `activate_on_reader`, and `activate_every`.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .inspect(|x| println!("{:?}", x));
+    ///     });
+    ///
+    ///     worker.run();
+    /// });
+    /// ```
+    pub fn run(&mut self) {
+        while self.has_dataflows() {
+            self.step_or_park(None);
+        }
+    }
+
+    /// Activates the operator at `address` as messages arrive at `receiver`.
+    ///
+    /// The messages are forwarded by a separate thread to the returned receiver, which the
+    /// operator should drain when activated. The operator is also activated once `receiver`
+    /// disconnects, at which point the returned receiver disconnects too. The thread exits
+    /// as soon as the returned receiver is dropped.
+    pub fn activate_on_receiver<T: Send+'static>(&mut self, address: &[usize], receiver: Receiver<T>) -> ExternalReceiver<T> {
+        let activator = self.sync_activator_for(address);
+        let (send, recv) = crossbeam_channel::unbounded();
+        let (shutdown, interrupt) = crossbeam_channel::bounded::<()>(0);
+        std::thread::Builder::new()
+            .name(format!("timely:source:{:?}", address))
+            .spawn(move || {
+                loop {
+                    crossbeam_channel::select! {
+                        recv(receiver) -> message => match message {
+                            Ok(message) => {
+                                if send.send(message).is_err() || activator.activate().is_err() {
+                                    return;
+                                }
+                            },
+                            Err(_) => break,
+                        },
+                        recv(interrupt) -> _ => return,
+                    }
+                }
+                // Alert the operator to the disconnection.
+                drop(send);
+                activator.activate().ok();
+            })
+            .expect("failed to spawn source thread");
+        ExternalReceiver { receiver: recv, _shutdown: shutdown }
+    }
+
+    /// Activates the operator at `address` as data are read from `reader`.
+    ///
+    /// The data are read by a separate thread, and forwarded to the returned receiver as they
+    /// are read. The operator is also activated once `reader` reaches its end or fails, after
+    /// forwarding any error, at which point the returned receiver disconnects.
+    ///
+    /// The thread blocks in `reader.read`, which cannot be interrupted: once the returned receiver
+    /// is dropped, the thread exits when its current read returns. A reader that may remain idle,
+    /// such as a socket or standard input, keeps the thread alive until it next produces data or
+    /// is closed. This method does not poll for readiness of the underlying file descriptor.
+    pub fn activate_on_reader<R: Read+Send+'static>(&mut self, address: &[usize], mut reader: R) -> ExternalReceiver<std::io::Result<Vec<u8>>> {
+        let activator = self.sync_activator_for(address);
+        let (send, recv) = crossbeam_channel::unbounded();
+        let (shutdown, interrupt) = crossbeam_channel::bounded::<()>(0);
+        std::thread::Builder::new()
+            .name(format!("timely:source:{:?}", address))
+            .spawn(move || {
+                let mut buffer = vec![0u8; 1 << 16];
+                while let Err(TryRecvError::Empty) = interrupt.try_recv() {
+                    let result = match reader.read(&mut buffer[..]) {
+                        Ok(0) => break,
+                        Ok(length) => Ok(buffer[.. length].to_vec()),
+                        Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                        Err(error) => Err(error),
+                    };
+                    let failed = result.is_err();
+                    if send.send(result).is_err() || activator.activate().is_err() || failed {
+                        break;
+                    }
+                }
+                // Alert the operator to the end of the data.
+                drop(send);
+                activator.activate().ok();
+            })
+            .expect("failed to spawn source thread");
+        ExternalReceiver { receiver: recv, _shutdown: shutdown }
+    }
+
+    /// Activates the operator at `address` every `period`, for as long as its dataflow exists.
+    ///
+    /// The worker is unparked as needed to perform the activations, which are delayed when
+    /// the worker is busy. The first activation happens after one `period`.
+    pub fn activate_every(&mut self, address: &[usize], period: Duration) {
+        assert!(period > Duration::new(0, 0), "timer period must be positive");
+        if address.is_empty() { panic!("Unacceptable address: Length zero"); }
+        self.timers.borrow_mut().push(Timer {
+            path: address.to_vec(),
+            period,
+            next: self.timer.elapsed() + period,
+        });
+    }
+
     // Acquire a new distinct dataflow identifier.
     fn allocate_dataflow_index(&mut self) -> usize {
         *self.dataflow_counter.borrow_mut() += 1;
@@ -727,6 +1526,13 @@ impl<A: Allocate> Clone for Worker<A> {
             activations: self.activations.clone(),
             active_dataflows: Vec::new(),
             temp_channel_ids: self.temp_channel_ids.clone(),
+            control: self.control.clone(),
+            recorder: self.recorder.clone(),
+            timers: self.timers.clone(),
+            accounting: self.accounting.clone(),
+            memory_log_next: self.memory_log_next,
+            counters: self.counters.clone(),
+            graph: self.graph.clone(),
         }
     }
 }
@@ -737,6 +1543,10 @@ struct Wrapper {
     operate: Option<Box<dyn Schedule>>,
     resources: Option<Box<dyn Any>>,
     channel_ids: Vec<usize>,
+    /// Scheduling priority; greater priorities are scheduled first.
+    priority: i64,
+    /// Set if the dataflow was active but not scheduled in the last step.
+    deferred: bool,
 }
 
 impl Wrapper {
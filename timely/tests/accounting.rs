@@ -0,0 +1,138 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::{CommunicationConfig, Config, WorkerConfig};
+use timely::accounting::{AccountKind, Usage};
+use timely::container::CapacityContainerBuilder;
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Inspect, Operator, Probe};
+use timely::logging::{MemoryEvent, TimelyEvent};
+use timely::worker::AsWorker;
+
+#[test]
+fn accounting_channels_and_inputs() {
+    let config = Config {
+        communication: CommunicationConfig::Thread,
+        worker: WorkerConfig::default().account_buffers(true),
+    };
+    timely::execute(config, |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .inspect(|_| { })
+                 .probe()
+        });
+
+        for round in 0 .. 100u64 {
+            input.send(round);
+        }
+        let usage = worker.dataflow_memory_usage();
+        assert_eq!(usage[&0].records, 100);
+        assert_eq!(usage[&0].bytes, 100 * std::mem::size_of::<u64>());
+
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+        assert_eq!(worker.dataflow_memory_usage()[&0], Usage::default());
+
+        drop(input);
+        while worker.step() { }
+        assert!(worker.dataflow_memory_usage().is_empty());
+    }).unwrap().join();
+}
+
+#[test]
+fn accounting_channels_without_buffers() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .inspect(|_| { });
+        });
+
+        // Records in the input handle are not accounted unless configured.
+        for round in 0 .. 100u64 {
+            input.send(round);
+        }
+        assert_eq!(worker.dataflow_memory_usage()[&0], Usage::default());
+
+        // Records flushed into the channel to `inspect` are.
+        input.advance_to(1);
+        assert_eq!(worker.dataflow_memory_usage()[&0].records, 100);
+    });
+}
+
+#[test]
+fn accounting_operator_state() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let address = Rc::new(RefCell::new(Vec::new()));
+        let address2 = address.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let scope2 = scope.clone();
+            scope.input_from(&mut input)
+                 .unary::<CapacityContainerBuilder<Vec<u64>>,_,_,_>(Pipeline, "Stash", move |_capability, info| {
+                     *address2.borrow_mut() = info.address.clone();
+                     let account = scope2.account_for(&info.address);
+                     let mut stash = Vec::new();
+                     move |input, _output| {
+                         input.for_each(|_time, data| {
+                             stash.extend(data.iter().cloned());
+                             account.set(Usage { records: stash.len(), bytes: stash.len() * 8 });
+                         });
+                     }
+                 })
+                 .probe()
+        });
+
+        for round in 0 .. 10u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+
+        let usage = worker.memory_usage();
+        assert_eq!(usage[&*address.borrow()], Usage { records: 10, bytes: 80 });
+        assert_eq!(worker.dataflow_memory_usage()[&0].records, 10);
+    });
+}
+
+#[test]
+fn accounting_logs_changes() {
+    let config = Config {
+        communication: CommunicationConfig::Thread,
+        worker: WorkerConfig::default().memory_log_interval(Some(Duration::from_millis(0))).account_buffers(true),
+    };
+    let events = timely::execute(config, |worker| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events2 = events.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            for (_, _, event) in data.drain(..) {
+                if let TimelyEvent::Memory(event) = event {
+                    events2.borrow_mut().push(event);
+                }
+            }
+        });
+
+        let mut input = InputHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .inspect(|_| { });
+        });
+
+        input.send(0);
+        worker.step();
+        drop(input);
+        while worker.step() { }
+        worker.log_register().remove("timely");
+
+        let events = events.borrow().clone();
+        events
+    }).unwrap().join().pop().unwrap().unwrap();
+
+    // The input handle holds the record, and then releases it.
+    let input: Vec<&MemoryEvent> = events.iter().filter(|event| event.kind == AccountKind::Input).collect();
+    assert_eq!(input.first().map(|event| event.records), Some(1));
+    assert_eq!(input.last().map(|event| event.records), Some(0));
+}
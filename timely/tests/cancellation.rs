@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use timely::communication::Allocate;
+use timely::dataflow::operators::{Exchange, Inspect};
+use timely::dataflow::operators::generic::operator::source;
+use timely::scheduling::Scheduler;
+use timely::worker::{AsWorker, Worker};
+
+/// Installs a dataflow that never completes, as its source holds a capability forever.
+fn endless<A: Allocate>(worker: &mut Worker<A>) {
+    worker.dataflow::<u64,_,_>(|scope| {
+        let peers = scope.peers() as u64;
+        source(scope, "Endless", |capability, info| {
+            let activator = scope.activator_for(&info.address[..]);
+            let mut round = 0;
+            move |output| {
+                output.session(&capability).give(round % peers);
+                round += 1;
+                activator.activate_after(Duration::from_millis(1));
+            }
+        })
+        .container::<Vec<_>>()
+        .exchange(|x| *x)
+        .inspect(|_x| { });
+    });
+}
+
+#[test]
+fn cancel_from_launching_thread() {
+    let guards = timely::execute(timely::Config::process(3), |worker| {
+        endless(worker);
+        worker.index()
+    }).unwrap();
+
+    std::thread::sleep(Duration::from_millis(100));
+    guards.cancel_handle().cancel();
+
+    let results = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(results, vec![0, 1, 2]);
+}
+
+#[test]
+fn cancel_from_worker() {
+    timely::execute(timely::Config::process(3), |worker| {
+        endless(worker);
+        for _ in 0 .. 10 {
+            worker.step();
+        }
+        if worker.index() == 0 {
+            worker.cancel();
+        }
+        while worker.has_dataflows() {
+            worker.step_or_park(None);
+        }
+        assert!(worker.is_cancelled());
+
+        // Dataflows constructed after cancellation are dropped immediately.
+        endless(worker);
+        assert!(!worker.has_dataflows());
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+}
@@ -0,0 +1,243 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Map, Probe};
+use timely::dataflow::operators::aggregation::{Aggregate, StateMachine};
+use timely::dataflow::operators::checkpoint::Checkpoint;
+
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("timely-checkpoint-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&directory).ok();
+    directory
+}
+
+/// Sums values by key through epochs up to `end`, resuming from any restored snapshot.
+///
+/// Requests a snapshot at `snapshot` unless one was restored, and returns the restored time
+/// and the most recent sum reported for each key.
+fn run(directory: &Path, snapshot: u64, end: u64) -> (Option<u64>, HashMap<u64, u64>) {
+    let directory = directory.to_path_buf();
+    let sums = Arc::new(Mutex::new(HashMap::new()));
+    let sums2 = sums.clone();
+    let restored = timely::execute(Config::process(2), move |worker| {
+
+        let checkpoint = Checkpoint::<u64>::new(worker, &directory);
+        let restored = checkpoint.restore().expect("failed to read checkpoints");
+
+        let sums = sums2.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .map(|x: u64| (x % 4, x))
+                 .state_machine_checkpointed(
+                     |key, val, sum: &mut u64| { *sum += val; (false, Some((*key, *sum))) },
+                     |key| *key,
+                     &checkpoint,
+                 )
+                 .inspect(move |&(key, sum)| {
+                     let mut sums = sums.lock().unwrap();
+                     let entry = sums.entry(key).or_insert(0);
+                     *entry = std::cmp::max(*entry, sum);
+                 })
+                 .probe()
+        });
+
+        if restored.is_none() {
+            checkpoint.snapshot_at(snapshot);
+        }
+
+        let start = restored.unwrap_or(0);
+        input.advance_to(start);
+        for epoch in start .. end {
+            if worker.index() == 0 {
+                for x in epoch * 10 .. (epoch + 1) * 10 {
+                    input.send(x);
+                }
+            }
+            input.advance_to(epoch + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+
+        if restored.is_none() && snapshot < end {
+            assert_eq!(checkpoint.completed(), Some(snapshot));
+        }
+        restored
+    }).unwrap().join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+
+    assert!(restored.iter().all(|time| time == &restored[0]));
+    let sums = sums.lock().unwrap().clone();
+    (restored[0], sums)
+}
+
+#[test]
+fn restore_resumes_from_snapshot() {
+    let directory = directory("resume");
+
+    // The first run stops after epoch 8, having completed a snapshot at epoch 5.
+    let (restored, _sums) = run(&directory, 5, 8);
+    assert_eq!(restored, None);
+
+    // The second run restores the state at epoch 5, and replays input from there.
+    let (restored, sums) = run(&directory, 5, 10);
+    assert_eq!(restored, Some(5));
+
+    let mut expected = HashMap::new();
+    for x in 0 .. 100u64 {
+        *expected.entry(x % 4).or_insert(0) += x;
+    }
+    assert_eq!(sums, expected);
+
+    std::fs::remove_dir_all(&directory).ok();
+}
+
+#[test]
+fn incomplete_snapshot_is_not_restored() {
+    let directory = directory("incomplete");
+
+    // The snapshot at epoch 20 is never completed.
+    let (restored, _sums) = run(&directory, 20, 8);
+    assert_eq!(restored, None);
+
+    let (restored, sums) = run(&directory, 20, 10);
+    assert_eq!(restored, None);
+    assert_eq!(sums.values().sum::<u64>(), (0 .. 100).sum::<u64>());
+
+    std::fs::remove_dir_all(&directory).ok();
+}
+
+#[test]
+fn staggered_workers_agree_on_snapshots() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let directory = directory("staggered");
+
+    // Worker 1 creates its checkpoint only once worker 0 has completed its snapshot.
+    let directory2 = directory.clone();
+    let done = Arc::new(AtomicBool::new(false));
+    timely::execute(Config::process(2), move |worker| {
+        if worker.index() == 1 {
+            while !done.load(Ordering::SeqCst) { std::thread::yield_now(); }
+        }
+        let checkpoint = Checkpoint::<u64>::new(worker, &directory2);
+        let registration = checkpoint.register::<u64>(0);
+        assert!(checkpoint.snapshot_at(5));
+        registration.write(&(worker.index() as u64));
+        assert_eq!(checkpoint.completed(), Some(5));
+        if worker.index() == 0 {
+            done.store(true, Ordering::SeqCst);
+        }
+    }).unwrap();
+
+    let directory2 = directory.clone();
+    let restored = timely::execute(Config::process(2), move |worker| {
+        let checkpoint = Checkpoint::<u64>::new(worker, &directory2);
+        let restored = checkpoint.restore().unwrap();
+        let state = checkpoint.register::<u64>(0).restored();
+        assert_eq!(state, Some(worker.index() as u64));
+        restored
+    }).unwrap().join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(restored, vec![Some(5), Some(5)]);
+
+    std::fs::remove_dir_all(&directory).ok();
+}
+
+#[test]
+fn dropped_registration_abandons_snapshot() {
+    let directory = directory("dropped");
+
+    timely::execute_directly({
+        let directory = directory.clone();
+        move |worker| {
+            let checkpoint = Checkpoint::<u64>::new(worker, &directory);
+            let written = checkpoint.register::<u64>(0);
+            let unwritten = checkpoint.register::<u64>(1);
+            assert!(checkpoint.snapshot_at(5));
+            written.write(&3);
+            drop(unwritten);
+            // The snapshot lacks the state of the dropped operator, and is not completed.
+            assert_eq!(checkpoint.pending(), None);
+            assert_eq!(checkpoint.completed(), None);
+            drop(written);
+            assert_eq!(Checkpoint::<u64>::new(worker, &directory).restore().unwrap(), None);
+        }
+    });
+
+    std::fs::remove_dir_all(&directory).ok();
+}
+
+/// Sums values by key within each epoch through epochs up to `end`, resuming from any restored snapshot.
+///
+/// Requests a snapshot at epoch 5 unless one was restored, and returns the restored time and the
+/// sums produced, as `(epoch, key, sum)`.
+fn run_aggregate(directory: &Path, end: u64) -> (Option<u64>, Vec<(u64, u64, u64)>) {
+    let directory = directory.to_path_buf();
+    timely::execute_directly(move |worker| {
+
+        let checkpoint = Checkpoint::<u64>::new(worker, &directory);
+        let restored = checkpoint.restore().expect("failed to read checkpoints");
+
+        let sums = Rc::new(RefCell::new(Vec::new()));
+        let sums2 = sums.clone();
+        let mut input = InputHandle::new();
+        worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .map(|x: u64| (x % 2, x))
+                 .aggregate_checkpointed(
+                     |_key, val, sum: &mut u64| { *sum += val; },
+                     |key, sum| (key, sum),
+                     |key| *key,
+                     &checkpoint,
+                 )
+                 .inspect_time(move |time, &(key, sum)| sums2.borrow_mut().push((*time, key, sum)));
+        });
+
+        if restored.is_none() {
+            checkpoint.snapshot_at(5);
+        }
+
+        let start = restored.unwrap_or(0);
+        input.advance_to(start);
+        for epoch in start .. end {
+            for x in epoch * 10 .. (epoch + 1) * 10 {
+                input.send(x);
+            }
+            input.advance_to(epoch + 1);
+            worker.step();
+        }
+        drop(input);
+        while worker.step() { }
+
+        let mut sums = sums.borrow().clone();
+        sums.sort();
+        (restored, sums)
+    })
+}
+
+#[test]
+fn aggregate_restores_held_aggregates() {
+    let directory = directory("aggregate");
+
+    // The first run completes a snapshot at epoch 5, holding the aggregates of earlier epochs.
+    let (restored, _sums) = run_aggregate(&directory, 7);
+    assert_eq!(restored, None);
+
+    // The second run produces the restored aggregates, and those of the epochs it replays.
+    let (restored, sums) = run_aggregate(&directory, 10);
+    assert_eq!(restored, Some(5));
+
+    let mut expected = Vec::new();
+    for epoch in 0 .. 10u64 {
+        for key in 0 .. 2u64 {
+            let sum = (epoch * 10 .. (epoch + 1) * 10).filter(|x| x % 2 == key).sum();
+            expected.push((epoch, key, sum));
+        }
+    }
+    assert_eq!(sums, expected);
+
+    std::fs::remove_dir_all(&directory).ok();
+}
@@ -0,0 +1,62 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::logging::TimelyEvent;
+use timely::logging::chrome::{ChromeTrace, TraceWriter};
+
+/// A writer into a buffer that outlives the trace.
+#[derive(Clone)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn chrome_trace_workers_operators_messages() {
+    let buffer = Shared(Arc::new(Mutex::new(Vec::new())));
+    let trace = TraceWriter::new(buffer.clone());
+
+    timely::execute(Config::process(2), move |worker| {
+        let mut sink = ChromeTrace::new(trace.clone());
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            sink.publish(data).unwrap();
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        for round in 0 .. 10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+        drop(input);
+        while worker.step() { }
+        worker.log_register().remove("timely");
+    }).unwrap();
+
+    let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(text.starts_with("[\n"));
+    assert!(text.ends_with("\n]\n"));
+    assert!(text.contains(r#""args":{"name":"worker 0"}"#));
+    assert!(text.contains(r#""args":{"name":"worker 1"}"#));
+    assert!(text.contains(r#""name":"InspectBatch","cat":"operator","ph":"B""#));
+    assert!(text.contains(r#""ph":"s""#));
+    assert!(text.contains(r#""ph":"f","bp":"e""#));
+
+    let begins = text.matches(r#""ph":"B""#).count();
+    let ends = text.matches(r#""ph":"E""#).count();
+    assert!(begins > 0);
+    assert_eq!(begins, ends);
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use timely::CommunicationConfig;
+use timely::config::{ConfigFile, Parameter};
+use timely::worker::ProgressMode;
+
+fn overrides(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn config_defaults() {
+    let config = ConfigFile::default().into_config().unwrap();
+    assert!(matches!(config.communication, CommunicationConfig::Thread));
+}
+
+#[test]
+fn config_overrides() {
+    let mut file = ConfigFile::default();
+    file.communication.threads = 2;
+    file.worker.step_budget_micros = Some(10);
+    file.worker.parameters = {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("answer".to_string(), Parameter::Integer(42));
+        parameters.insert("name".to_string(), Parameter::String("timely".to_string()));
+        parameters
+    };
+
+    file.apply_overrides(overrides(&[
+        ("TIMELY_THREADS", "4"),
+        ("TIMELY_ZEROCOPY", "true"),
+        ("TIMELY_PROGRESS_MODE", "eager"),
+        ("TIMELY_WORKER_LOG_ADDR", "localhost:8000"),
+        ("TIMELY_MEMORY_LOG_INTERVAL", "1000000"),
+        ("TIMELY_ACCOUNT_BUFFERS", "true"),
+        ("UNRELATED", "ignored"),
+    ])).unwrap();
+    assert_eq!(file.communication.threads, 4);
+    assert_eq!(file.worker.progress_mode, Some(ProgressMode::Eager));
+    assert_eq!(file.worker.step_budget_micros, Some(10));
+    assert_eq!(file.worker.memory_log_interval_micros, Some(1_000_000));
+    assert!(file.worker.account_buffers);
+    assert_eq!(file.logging.timely.as_deref(), Some("localhost:8000"));
+
+    let config = file.into_config().unwrap();
+    assert!(matches!(config.communication, CommunicationConfig::ProcessBinary(4)));
+    assert_eq!(config.worker.get::<i64>("answer"), Some(&42));
+    assert_eq!(config.worker.get::<String>("name").map(|s| s.as_str()), Some("timely"));
+}
+
+#[test]
+fn config_cluster() {
+    let mut file = ConfigFile::default();
+    file.apply_overrides(overrides(&[
+        ("TIMELY_ADDRESSES", "host0:2101, host1:2101"),
+        ("TIMELY_PROCESS", "1"),
+    ])).unwrap();
+    match file.clone().into_config().unwrap().communication {
+        CommunicationConfig::Cluster { process, addresses, .. } => {
+            assert_eq!(process, 1);
+            assert_eq!(addresses, vec!["host0:2101".to_string(), "host1:2101".to_string()]);
+        },
+        _ => panic!("expected a cluster configuration"),
+    }
+
+    file.communication.process = 2;
+    assert!(file.into_config().is_err());
+}
+
+#[test]
+fn config_invalid_override() {
+    let mut file = ConfigFile::default();
+    assert!(file.apply_overrides(overrides(&[("TIMELY_THREADS", "many")])).is_err());
+    assert!(file.apply_overrides(overrides(&[("TIMELY_PROGRESS_MODE", "eventually")])).is_err());
+}
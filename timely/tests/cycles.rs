@@ -0,0 +1,98 @@
+use timely::dataflow::{ProbeHandle, Scope};
+use timely::dataflow::operators::{Concat, ConnectLoop, Enter, Feedback, Filter, Leave, Map, Probe, ToStream};
+
+#[test]
+#[should_panic(expected = "contains 1 cycle(s) whose path summaries do not advance timestamps")]
+fn default_feedback_summary() {
+    timely::execute_directly(|worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback::<Vec<u64>>(Default::default());
+            (0 .. 10u64)
+                .to_stream(scope)
+                .concat(&cycle)
+                .map(|x| x + 1)
+                .connect_loop(handle);
+        });
+    });
+}
+
+#[test]
+fn advancing_feedback_summary() {
+    timely::execute_directly(|worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback::<Vec<u64>>(1);
+            (0 .. 10u64)
+                .to_stream(scope)
+                .concat(&cycle)
+                .map(|x| x + 1)
+                .filter(|x| *x < 100)
+                .connect_loop(handle);
+        });
+    });
+}
+
+#[test]
+fn try_dataflow_reports_cycle() {
+    timely::execute_directly(|worker| {
+        let result = worker.try_dataflow::<u64,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback::<Vec<u64>>(Default::default());
+            (0 .. 10u64)
+                .to_stream(scope)
+                .concat(&cycle)
+                .map(|x| x + 1)
+                .connect_loop(handle);
+        });
+        let error = result.unwrap_err();
+        assert!(error.contains("contains 1 cycle(s) whose path summaries do not advance timestamps"));
+        assert!(error.contains("Feedback"));
+
+        // The dataflow is not installed, and the worker is free to construct others.
+        assert!(!worker.has_dataflows());
+        assert!(worker.dataflow_graph().operators().is_empty());
+        worker.dataflow::<u64,_,_>(|scope| { (0 .. 10u64).to_stream(scope); });
+        while worker.step() { }
+    });
+}
+
+#[test]
+fn try_scoped_reports_cycle() {
+    timely::execute_directly(|worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let result = scope.try_scoped::<u64,_,_>("Cyclic", |inner| {
+                let (handle, cycle) = inner.feedback::<Vec<u64>>(Default::default());
+                (0 .. 10u64)
+                    .to_stream(inner)
+                    .concat(&cycle)
+                    .connect_loop(handle);
+            });
+            assert!(result.unwrap_err().contains("Feedback"));
+
+            let result = scope.try_scoped::<u64,_,_>("Acyclic", |inner| {
+                (0 .. 10u64).to_stream(inner).filter(|x| *x < 5);
+            });
+            assert!(result.is_ok());
+        });
+    });
+}
+
+#[test]
+fn try_scoped_reports_cycle_across_boundary() {
+    timely::execute_directly(|worker| {
+        let mut probe = ProbeHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            let stream = (0 .. 10u64).to_stream(scope);
+            let result = scope.try_scoped::<u64,_,_>("Cyclic", |inner| {
+                let (handle, cycle) = inner.feedback::<Vec<u64>>(Default::default());
+                let looped = stream.enter(inner).concat(&cycle);
+                looped.connect_loop(handle);
+                looped.leave().probe_with(&mut probe);
+            });
+            assert!(result.unwrap_err().contains("Feedback"));
+
+            // Streams that entered or left the failed scope do not prevent the dataflow's construction.
+            stream.map(|x| x + 1).probe_with(&mut probe);
+        });
+        while worker.step() { }
+        assert!(probe.done());
+    });
+}
@@ -0,0 +1,74 @@
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::operators::{Enter, Exchange, Input, Inspect, Leave, Probe};
+
+#[test]
+fn dataflow_graph_operators_and_channels() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| {
+            let stream = scope.input_from(&mut input);
+            scope.region_named("Nested", |inner| {
+                stream.enter(inner)
+                      .exchange(|x| *x)
+                      .leave()
+            })
+            .inspect(|_| { })
+            .probe()
+        });
+
+        let graph = worker.dataflow_graph();
+        let operators = graph.operators();
+
+        let dataflow = &operators[&vec![0]];
+        assert_eq!((dataflow.inputs, dataflow.outputs), (0, 0));
+
+        let region = operators.values().find(|operator| operator.name == "Nested").unwrap();
+        assert_eq!(region.addr.len(), 2);
+        assert_eq!((region.inputs, region.outputs), (1, 1));
+
+        let exchange = operators.values().find(|operator| operator.name == "Exchange").unwrap();
+        assert!(exchange.addr.starts_with(&region.addr));
+        assert_eq!((exchange.inputs, exchange.outputs), (1, 1));
+
+        // The exchange is connected by its contract, and the region's boundaries directly.
+        let channels = graph.channels();
+        let into_exchange = channels.values().find(|channel| channel.target_addr() == exchange.addr).unwrap();
+        assert_eq!(into_exchange.pact, Some("Exchange"));
+        assert_eq!(into_exchange.source_addr(), region.addr);
+        assert!(channels.values().any(|channel| channel.target_addr() == region.addr && channel.pact.is_none()));
+        assert!(channels.values().any(|channel| channel.pact == Some("Pipeline")));
+
+        let dot = graph.dot();
+        assert!(dot.contains("subgraph cluster_op_0 {"));
+        assert!(dot.contains(&format!("subgraph cluster_op_0_{} {{", region.addr[1])));
+        assert!(dot.contains("[label=\"Exchange\", style=bold];"));
+
+        let json = graph.json();
+        assert!(json.starts_with("{\"operators\":["));
+        assert!(json.contains(&format!("\"addr\":{:?},\"name\":\"Exchange\",\"inputs\":1,\"outputs\":1", exchange.addr)));
+        assert!(json.contains("\"pact\":null"));
+
+        // The graph of a dataflow is discarded once it completes.
+        drop(input);
+        worker.step_while(|| !probe.done());
+        while worker.step() { }
+        assert!(worker.dataflow_graph().operators().is_empty());
+    });
+}
+
+#[test]
+fn dataflow_graph_forgets_dropped_dataflows() {
+    timely::execute_directly(|worker| {
+        let mut input1 = InputHandle::<u64, u64>::new();
+        let mut input2 = InputHandle::<u64, u64>::new();
+        worker.dataflow(|scope| { scope.input_from(&mut input1).inspect(|_| { }); });
+        worker.dataflow(|scope| { scope.input_from(&mut input2).inspect(|_| { }); });
+
+        // Only the operators of the remaining dataflow are described.
+        worker.drop_dataflow(0);
+        let graph = worker.dataflow_graph();
+        assert!(!graph.operators().is_empty());
+        assert!(graph.operators().keys().all(|addr| addr[0] == 1));
+        assert!(graph.channels().values().all(|channel| channel.scope_addr[0] == 1));
+    });
+}
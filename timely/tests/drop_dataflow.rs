@@ -0,0 +1,144 @@
+use std::sync::{Arc, Mutex};
+
+use timely::{CommunicationConfig, Config, WorkerConfig};
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::logging::TimelyEvent;
+
+fn config(communication: CommunicationConfig) -> Config {
+    Config { communication, worker: WorkerConfig::default() }
+}
+
+/// Drops an exchanging dataflow from one worker while data are in flight, and
+/// checks that all workers drop it and that a later dataflow is unaffected.
+fn drop_during_exchange(communication: CommunicationConfig) {
+    let received = Arc::new(Mutex::new(0));
+    let received2 = received.clone();
+    timely::execute(config(communication), move |worker| {
+
+        let index = worker.index();
+        let mut input = InputHandle::new();
+        let dropped = worker.next_dataflow_index();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|x: &u64| *x)
+                .inspect(|_x| { });
+        });
+
+        for round in 0 .. 10 {
+            for x in 0 .. 1000 {
+                input.send(x);
+            }
+            input.advance_to(round + 1);
+            worker.step();
+            if round == 5 && index == 0 {
+                worker.drop_dataflow(dropped);
+            }
+        }
+
+        // The input remains open, so the dataflow only ends by being dropped.
+        while worker.has_dataflows() {
+            worker.step();
+        }
+        drop(input);
+
+        let received = received2.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|x: &u64| *x)
+                .inspect(move |_x| *received.lock().unwrap() += 1)
+                .probe()
+        });
+        if index == 0 {
+            for x in 0 .. 1000 {
+                input.send(x);
+            }
+        }
+        input.advance_to(1);
+        while probe.less_than(input.time()) {
+            worker.step();
+        }
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+
+    assert_eq!(*received.lock().unwrap(), 1000);
+}
+
+#[test]
+fn drop_during_exchange_process() {
+    drop_during_exchange(CommunicationConfig::Process(3));
+}
+
+#[test]
+fn drop_during_exchange_process_binary() {
+    drop_during_exchange(CommunicationConfig::ProcessBinary(3));
+}
+
+#[test]
+fn drop_before_construction() {
+    timely::execute(config(CommunicationConfig::Process(3)), |worker| {
+        // The first worker drops the dataflow before anyone constructs it.
+        let dropped = worker.next_dataflow_index();
+        if worker.index() == 0 {
+            worker.drop_dataflow(dropped);
+        }
+        let mut input = InputHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|x: &u64| *x)
+                .inspect(|_x| { });
+        });
+        input.send(0);
+        while worker.has_dataflows() {
+            worker.step();
+        }
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+}
+
+#[test]
+fn drop_logs_shutdown() {
+    let shutdowns = Arc::new(Mutex::new(Vec::new()));
+    let shutdowns2 = shutdowns.clone();
+    timely::execute(config(CommunicationConfig::Process(2)), move |worker| {
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events2 = events.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            events2.lock().unwrap().extend(data.drain(..).map(|(_, _, event)| event));
+        });
+
+        let mut input = InputHandle::new();
+        let dropped = worker.next_dataflow_index();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|x: &u64| *x)
+                .inspect(|_x| { });
+        });
+        input.send(worker.index() as u64);
+        worker.step();
+        if worker.index() == 1 {
+            worker.drop_dataflow(dropped);
+        }
+        while worker.has_dataflows() {
+            worker.step();
+        }
+        worker.log_register().flush();
+
+        let events = events.lock().unwrap();
+        let dataflow_id = events.iter().find_map(|event| match event {
+            TimelyEvent::Operates(operates) if operates.addr == vec![dropped] => Some(operates.id),
+            _ => None,
+        }).expect("dataflow not logged");
+        let shutdown = events.iter().any(|event| match event {
+            TimelyEvent::Shutdown(shutdown) => shutdown.id == dataflow_id,
+            _ => false,
+        });
+        shutdowns2.lock().unwrap().push(shutdown);
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+
+    assert_eq!(*shutdowns.lock().unwrap(), vec![true, true]);
+}
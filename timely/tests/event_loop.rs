@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crossbeam_channel::TryRecvError;
+
+use timely::communication::Allocate;
+use timely::dataflow::operators::generic::operator::source;
+use timely::worker::{ExternalReceiver, Worker};
+
+/// Installs a source that hands each message of a receiver to `handle`, until it disconnects.
+///
+/// Returns the address of the source, and a slot for the receiver it reads from.
+fn external<A: Allocate, T: 'static>(worker: &mut Worker<A>, mut handle: impl FnMut(T)+'static) -> (Vec<usize>, Rc<RefCell<Option<ExternalReceiver<T>>>>) {
+    let receiver = Rc::new(RefCell::new(None::<ExternalReceiver<T>>));
+    let receiver2 = receiver.clone();
+    let address = worker.dataflow::<u64,_,_>(|scope| {
+        let mut address = Vec::new();
+        let address_mut = &mut address;
+        source(scope, "External", move |capability, info| {
+            *address_mut = info.address.clone();
+            let mut capability = Some(capability);
+            move |output| {
+                if let Some(receiver) = receiver2.borrow().as_ref() {
+                    loop {
+                        match receiver.try_recv() {
+                            Ok(message) => {
+                                handle(message);
+                                output.session(capability.as_ref().unwrap()).give(());
+                            },
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => { capability = None; break; },
+                        }
+                    }
+                }
+            }
+        });
+        address
+    });
+    (address, receiver)
+}
+
+#[test]
+fn receiver_activates_operator() {
+    timely::execute_directly(|worker| {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        let (address, slot) = external(worker, move |x: u64| received2.borrow_mut().push(x));
+
+        let (send, recv) = crossbeam_channel::unbounded();
+        *slot.borrow_mut() = Some(worker.activate_on_receiver(&address[..], recv));
+        let sender = std::thread::spawn(move || {
+            for x in 0 .. 100 {
+                send.send(x).unwrap();
+                if x % 10 == 0 { std::thread::sleep(Duration::from_millis(1)); }
+            }
+        });
+
+        // The worker parks until messages arrive, and completes once the sender disconnects.
+        worker.run();
+        sender.join().unwrap();
+        assert_eq!(*received.borrow(), (0 .. 100).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn dropped_receiver_stops_thread() {
+    timely::execute_directly(|worker| {
+        let (send, recv) = crossbeam_channel::unbounded::<u64>();
+        let forwarded = worker.activate_on_receiver(&[0], recv);
+
+        // Once the forwarded receiver is dropped, the thread exits without awaiting a message,
+        // dropping the receiver it forwards from.
+        drop(forwarded);
+        let mut attempts = 0;
+        while send.send(0).is_ok() {
+            attempts += 1;
+            assert!(attempts < 1000, "source thread did not exit");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    });
+}
+
+#[test]
+fn reader_activates_operator() {
+    timely::execute_directly(|worker| {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        let (address, slot) = external(worker, move |bytes: std::io::Result<Vec<u8>>| {
+            received2.borrow_mut().extend(bytes.unwrap());
+        });
+
+        let data = (0 .. 200_000u32).map(|x| x as u8).collect::<Vec<_>>();
+        *slot.borrow_mut() = Some(worker.activate_on_reader(&address[..], std::io::Cursor::new(data.clone())));
+        worker.run();
+        assert_eq!(*received.borrow(), data);
+    });
+}
+
+/// Installs a source that stops after its sixth activation, counting activations in `activations`.
+///
+/// Returns the address of the source.
+fn ticks<A: Allocate>(worker: &mut Worker<A>, activations: Rc<RefCell<usize>>) -> Vec<usize> {
+    worker.dataflow::<u64,_,_>(|scope| {
+        let mut address = Vec::new();
+        let address_mut = &mut address;
+        source(scope, "Ticks", move |capability, info| {
+            *address_mut = info.address.clone();
+            let mut capability = Some(capability);
+            move |output| {
+                *activations.borrow_mut() += 1;
+                if *activations.borrow() > 5 {
+                    capability = None;
+                }
+                else {
+                    output.session(capability.as_ref().unwrap()).give(());
+                }
+            }
+        });
+        address
+    })
+}
+
+#[test]
+fn timer_activates_operator() {
+    timely::execute_directly(|worker| {
+        let activations = Rc::new(RefCell::new(0));
+        let address = ticks(worker, activations.clone());
+        worker.activate_every(&address[..], Duration::from_millis(5));
+        // Without the timer, the worker would park indefinitely after the first activation.
+        worker.run();
+        assert_eq!(*activations.borrow(), 6);
+    });
+}
+
+#[test]
+fn timer_registered_through_clone() {
+    timely::execute_directly(|worker| {
+        let activations = Rc::new(RefCell::new(0));
+        let address = ticks(worker, activations.clone());
+        // Operators hold clones of the worker; their timers fire as if registered directly.
+        worker.clone().activate_every(&address[..], Duration::from_millis(5));
+        worker.run();
+        assert_eq!(*activations.borrow(), 6);
+    });
+}
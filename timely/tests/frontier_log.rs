@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::container::CapacityContainerBuilder;
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Inspect, Operator, Probe};
+use timely::logging::{FrontierEvent, TimelyEvent};
+
+#[test]
+fn frontier_lag_of_held_capability() {
+    timely::execute_directly(|worker| {
+        let names = Rc::new(RefCell::new(HashMap::new()));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (names2, events2) = (names.clone(), events.clone());
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            for (_, _, event) in data.drain(..) {
+                match event {
+                    TimelyEvent::Operates(event) => { names2.borrow_mut().insert(event.name, event.id); },
+                    TimelyEvent::Frontier(event) => { events2.borrow_mut().push(event); },
+                    _ => { },
+                }
+            }
+        });
+
+        // "Hold" retains its initial capability until its input is exhausted, so that the
+        // frontier of the operator downstream of it lags the input.
+        let mut input = InputHandle::new();
+        let input_probe = ProbeHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .probe_with(&input_probe)
+                 .unary_frontier::<CapacityContainerBuilder<Vec<u64>>,_,_,_>(Pipeline, "Hold", |capability, _info| {
+                     let mut held = Some(capability);
+                     move |input, _output| {
+                         input.for_each(|_time, _data| { });
+                         if input.frontier().is_empty() { held.take(); }
+                     }
+                 })
+                 .inspect(|_| { })
+                 .probe()
+        });
+
+        for round in 0 .. 5 {
+            input.advance_to(round + 1);
+            std::thread::sleep(Duration::from_millis(1));
+            worker.step_while(|| input_probe.less_than(&(round + 1)));
+        }
+
+        let hold = names.borrow()["Hold"];
+        let inspect = names.borrow()["InspectBatch"];
+        let of = |id: usize| -> Vec<FrontierEvent> { events.borrow().iter().filter(|event| event.id == id).cloned().collect() };
+
+        // The frontier of "Hold" keeps up with the input.
+        let last = of(hold).pop().unwrap();
+        assert_eq!(last.frontiers, vec![vec!["5".to_owned()]]);
+        assert_eq!(last.inputs, vec!["5".to_owned()]);
+        assert_eq!(last.lag, vec![Duration::default()]);
+
+        // The frontier of the inspect operator does not, and is reported as the input advances.
+        let lagging = of(inspect);
+        assert!(lagging.len() >= 5);
+        let last = lagging.last().unwrap();
+        assert_eq!(last.frontiers, vec![vec!["0".to_owned()]]);
+        assert_eq!(last.inputs, vec!["5".to_owned()]);
+        assert!(last.lag[0] >= Duration::from_millis(4));
+
+        // Once the input closes, all frontiers catch up.
+        drop(input);
+        worker.step_while(|| !probe.done());
+        let last = of(inspect).pop().unwrap();
+        assert_eq!(last.frontiers, vec![Vec::<String>::new()]);
+        assert_eq!(last.lag, vec![Duration::default()]);
+    });
+}
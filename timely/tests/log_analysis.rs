@@ -0,0 +1,76 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::dataflow::operators::capture::EventWriter;
+use timely::logging::{BatchLogger, TimelyEvent};
+use timely::logging::analysis::Analysis;
+
+/// A writer into a buffer that outlives the worker.
+#[derive(Clone)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn log_analysis_report_and_dot() {
+    let logs: Vec<Shared> = (0 .. 2).map(|_| Shared(Arc::new(Mutex::new(Vec::new())))).collect();
+    let logs2 = logs.clone();
+
+    timely::execute(Config::process(2), move |worker| {
+        let mut logger = BatchLogger::new(EventWriter::new(logs2[worker.index()].clone()));
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |time, data| {
+            logger.publish_batch(time, data);
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        if worker.index() == 0 {
+            for round in 0 .. 100 {
+                input.send(round);
+            }
+        }
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+        worker.log_register().remove("timely");
+    }).unwrap();
+
+    let mut analysis = Analysis::new();
+    for log in logs.iter() {
+        let bytes = log.0.lock().unwrap().clone();
+        analysis.read(&bytes[..]).unwrap();
+    }
+
+    assert_eq!(analysis.workers().keys().cloned().collect::<Vec<_>>(), vec![0, 1]);
+    let inspect = analysis.operators().values().find(|operator| operator.name == "InspectBatch").unwrap();
+    assert!(inspect.activations > 0);
+    assert_eq!(inspect.busy.len(), 2);
+
+    // All records arrive at the inspect operator on one channel.
+    let channel = analysis.channels().values()
+        .find(|channel| channel.scope_addr.len() == 1 && channel.target.0 == inspect.addr[1])
+        .unwrap();
+    assert_eq!(channel.records, 100);
+    assert_eq!(channel.bytes, 100 * std::mem::size_of::<u64>() as u64);
+
+    let report = analysis.report();
+    assert!(report.contains("InspectBatch"));
+    assert!(report.contains("worker skew: "));
+
+    let dot = analysis.dot();
+    assert!(dot.starts_with("digraph dataflow {"));
+    assert!(dot.contains("subgraph cluster_op_0 {"));
+    assert!(dot.contains(&format!("-> op_0_{} [label=\"100 records\"];", inspect.addr[1])));
+}
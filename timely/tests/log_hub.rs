@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::dataflow::operators::capture::{Event, EventReader, EventWriter};
+use timely::dataflow::operators::capture::event::EventIterator;
+use timely::logging::TimelyEvent;
+use timely::logging::hub::LogHub;
+
+/// A writer into a buffer that outlives the workers.
+#[derive(Clone)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn log_hub_combines_workers() {
+    let log = Shared(Arc::new(Mutex::new(Vec::new())));
+    let hub = LogHub::new(0 .. 3, EventWriter::new(log.clone()));
+
+    timely::execute(Config::process(3), move |worker| {
+        let mut sink = hub.sink(worker.index());
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |time, data| {
+            sink.publish_batch(time, data);
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        for round in 0 .. 10u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+        worker.log_register().remove("timely");
+    }).unwrap();
+
+    let bytes = log.0.lock().unwrap().clone();
+    let mut reader = EventReader::<Duration, Vec<(Duration, usize, TimelyEvent)>, _>::new(&bytes[..]);
+
+    let mut latest = HashMap::new();
+    let mut frontier = Duration::default();
+    let mut capabilities = 1;
+    let mut misses = 0;
+    while misses < 2 {
+        match reader.next() {
+            Some(Event::Messages(time, data)) => {
+                misses = 0;
+                assert!(*time >= frontier);
+                for (event_time, worker, _) in data.iter() {
+                    let latest = latest.entry(*worker).or_insert(*event_time);
+                    assert!(*event_time >= *latest);
+                    *latest = *event_time;
+                }
+            },
+            Some(Event::Progress(updates)) => {
+                misses = 0;
+                for (time, diff) in updates.iter() {
+                    if *diff > 0 { assert!(*time >= frontier); frontier = *time; }
+                    capabilities += diff;
+                }
+            },
+            None => misses += 1,
+        }
+    }
+
+    // One stream holds the events of all workers, and completes.
+    assert_eq!(latest.keys().count(), 3);
+    assert_eq!(capabilities, 0);
+}
@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use timely::communication::Allocate;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::logging::{StartStop, TimelyEvent};
+use timely::logging_core::Sample;
+use timely::worker::Worker;
+
+/// Constructs a dataflow that exchanges and inspects records, and runs it for 100 rounds.
+///
+/// Returns the number of steps taken.
+fn run<A: Allocate>(worker: &mut Worker<A>, before: impl FnOnce(&mut Worker<A>)) -> usize {
+    let mut input = InputHandle::new();
+    let probe = worker.dataflow::<u64,_,_>(|scope| {
+        scope.input_from(&mut input)
+             .exchange(|x| *x)
+             .inspect(|_| { })
+             .probe()
+    });
+    before(worker);
+
+    let mut steps = 0;
+    for round in 0 .. 100u64 {
+        input.send(round);
+        input.advance_to(round + 1);
+        while probe.less_than(&(round + 1)) {
+            worker.step();
+            steps += 1;
+        }
+    }
+    steps
+}
+
+#[test]
+fn logging_filtered() {
+    timely::execute_directly(|worker| {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let messages2 = messages.clone();
+        let channel = Rc::new(RefCell::new(None));
+        let channel2 = channel.clone();
+        worker.log_register().insert_filtered::<TimelyEvent,_,_>(
+            "timely",
+            move |event| match (event, *channel2.borrow()) {
+                (TimelyEvent::Messages(event), Some(channel)) => event.channel == channel,
+                _ => false,
+            },
+            move |_time, data| messages2.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event)),
+        );
+
+        // Only log messages on the channel into the inspect operator.
+        run(worker, |worker| {
+            let graph = worker.dataflow_graph();
+            let inspect = graph.operators().values().find(|operator| operator.name == "InspectBatch").unwrap();
+            let into_inspect = graph.channels().values().find(|channel| channel.target_addr() == inspect.addr).unwrap();
+            *channel.borrow_mut() = Some(into_inspect.id);
+        });
+        worker.log_register().remove("timely");
+
+        let channel = channel.borrow().unwrap();
+        let messages = messages.borrow();
+        assert!(!messages.is_empty());
+        assert!(messages.iter().all(|event| match event {
+            TimelyEvent::Messages(event) => event.channel == channel,
+            _ => false,
+        }));
+    });
+}
+
+#[test]
+fn logging_sampled() {
+    timely::execute_directly(|worker| {
+        let schedules = Rc::new(RefCell::new(Vec::new()));
+        let schedules2 = schedules.clone();
+        let mut sample = Sample::new(0.01);
+        // Operators whose sampled scheduling has started, and whose stop is to be selected.
+        let mut started = HashSet::new();
+        worker.log_register().insert_filtered::<TimelyEvent,_,_>(
+            "timely",
+            move |event| match event {
+                TimelyEvent::Schedule(event) => match event.start_stop {
+                    StartStop::Start => sample.select() && started.insert(event.id),
+                    StartStop::Stop => started.remove(&event.id),
+                },
+                _ => false,
+            },
+            move |_time, data| schedules2.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event)),
+        );
+
+        let steps = run(worker, |_| { });
+        worker.log_register().remove("timely");
+
+        // Each sampled scheduling is logged with both its start and its stop.
+        let schedules = schedules.borrow();
+        assert!(!schedules.is_empty());
+        assert!(schedules.len() < steps * 2);
+        let mut open = HashSet::new();
+        for event in schedules.iter() {
+            match event {
+                TimelyEvent::Schedule(event) => match event.start_stop {
+                    StartStop::Start => assert!(open.insert(event.id)),
+                    StartStop::Stop => assert!(open.remove(&event.id)),
+                },
+                _ => panic!("unexpected event: {:?}", event),
+            }
+        }
+        assert!(open.is_empty());
+    });
+}
+
+#[test]
+fn sample_rates() {
+    let mut all = Sample::new(1.0);
+    assert!((0 .. 10).all(|_| all.select()));
+    let mut none = Sample::new(0.0);
+    assert!((0 .. 10).all(|_| !none.select()));
+    let mut quarter = Sample::new(0.25);
+    assert_eq!((0 .. 400).filter(|_| quarter.select()).count(), 100);
+}
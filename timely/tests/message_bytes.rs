@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::Container;
+use timely::container::columnation::TimelyStack;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::logging::{MessagesEvent, TimelyEvent};
+
+#[test]
+fn message_bytes_logged() {
+    let events = timely::execute_directly(|worker| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events2 = events.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            for (_, _, event) in data.drain(..) {
+                if let TimelyEvent::Messages(event) = event {
+                    events2.borrow_mut().push(event);
+                }
+            }
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        for round in 0 .. 10u64 {
+            input.send(round);
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+        worker.log_register().remove("timely");
+
+        let events = events.borrow().clone();
+        events
+    });
+
+    let sent: Vec<&MessagesEvent> = events.iter().filter(|event| event.is_send).collect();
+    assert!(!sent.is_empty());
+    for event in events.iter() {
+        assert_eq!(event.bytes, Some(event.length * std::mem::size_of::<u64>()));
+    }
+}
+
+#[test]
+fn size_in_bytes_containers() {
+    let vector = vec![0u64; 10];
+    assert_eq!(vector.size_in_bytes(), Some(80));
+    assert_eq!(std::rc::Rc::new(vector).size_in_bytes(), Some(80));
+
+    // Columnar containers count the memory owned by their records.
+    let strings: Vec<String> = (0 .. 10).map(|i| i.to_string().repeat(10)).collect();
+    let stack: TimelyStack<String> = strings.iter().collect();
+    let size = stack.size_in_bytes().unwrap();
+    assert!(size >= 10 * std::mem::size_of::<String>() + 100);
+}
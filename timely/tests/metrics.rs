@@ -0,0 +1,113 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use timely::Config;
+use timely::communication::logging::{CommunicationEvent, CommunicationSetup, MessageEvent};
+use timely::communication::networking::MessageHeader;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::logging::TimelyEvent;
+use timely::logging::metrics::Metrics;
+
+/// Scrapes the metrics served at `address`, returning the body of the response.
+fn scrape(address: std::net::SocketAddr) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    response.split("\r\n\r\n").nth(1).unwrap().to_owned()
+}
+
+#[test]
+fn metrics_scrape_workers() {
+    let metrics = Metrics::new();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    metrics.serve(listener);
+
+    let metrics2 = metrics.clone();
+    timely::execute(Config::process(2), move |worker| {
+        let mut sink = metrics2.timely();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            sink.publish(data);
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        for round in 0 .. 10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+        drop(input);
+        while worker.step() { }
+        worker.log_register().remove("timely");
+    }).unwrap();
+
+    let text = scrape(address);
+    assert_eq!(text, metrics.render());
+    assert!(text.contains("# TYPE timely_operator_schedule_seconds histogram\n"));
+    assert!(text.contains(r#"name="InspectBatch",le="+Inf"}"#));
+    for worker in 0 .. 2 {
+        let inspect = format!("timely_operator_schedule_seconds_count{{worker=\"{}\",", worker);
+        assert!(text.lines().any(|line| line.starts_with(&inspect) && line.contains("InspectBatch")));
+    }
+
+    // Each worker sends all of its records, and receives the records exchanged to it.
+    let records = |direction: &str| -> u64 {
+        text.lines()
+            .filter(|line| line.starts_with("timely_channel_records_total{") && line.contains(direction))
+            .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+            .sum()
+    };
+    assert!(records("direction=\"send\"") >= 20);
+    assert_eq!(records("direction=\"send\""), records("direction=\"recv\""));
+}
+
+#[test]
+fn metrics_communication_bytes() {
+    let metrics = Metrics::new();
+    let mut sink = metrics.communication();
+    let setup = CommunicationSetup { sender: true, process: 0, remote: Some(1) };
+    let message = |is_send, length| {
+        let header = MessageHeader { channel: 0, source: 0, target: 1, length, seqno: 0 };
+        CommunicationEvent::Message(MessageEvent { is_send, header })
+    };
+    let mut data = vec![
+        (Duration::default(), setup, message(true, 100)),
+        (Duration::default(), setup, message(true, 28)),
+        (Duration::default(), setup, message(false, 64)),
+    ];
+    sink.publish(&mut data);
+    assert!(data.is_empty());
+
+    let text = metrics.render();
+    assert!(text.contains("timely_communication_bytes_total{process=\"0\",peer=\"1\",direction=\"send\"} 128\n"));
+    assert!(text.contains("timely_communication_bytes_total{process=\"0\",peer=\"1\",direction=\"recv\"} 64\n"));
+    assert!(text.contains("timely_communication_messages_total{process=\"0\",peer=\"1\",direction=\"send\"} 2\n"));
+}
+
+#[test]
+fn metrics_reject_large_requests() {
+    let metrics = Metrics::new();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    metrics.serve(listener);
+
+    // A request that never ends is cut off, without a response.
+    let mut stream = TcpStream::connect(address).unwrap();
+    let _ = stream.write_all(&vec![b'x'; 1 << 16]);
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response);
+    assert!(response.is_empty());
+
+    // Later scrapes are still answered.
+    assert_eq!(scrape(address), metrics.render());
+}
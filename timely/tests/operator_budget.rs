@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use timely::WorkerConfig;
+use timely::communication::allocator::Thread;
+use timely::container::CapacityContainerBuilder;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Inspect, ToStream};
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::worker::Worker;
+
+#[test]
+fn fuel_bounds_work_per_invocation() {
+
+    let invocations = Rc::new(RefCell::new(Vec::new()));
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let config = WorkerConfig::default().operator_fuel(Some(10));
+    let mut worker = Worker::new(config, Thread::new());
+    let invocations2 = invocations.clone();
+    let received2 = received.clone();
+    worker.dataflow::<u64,_,_>(move |scope| {
+        let stream = (0 .. 1000u64).to_stream(scope);
+        let mut builder = OperatorBuilder::new("Budgeted".to_owned(), scope.clone());
+        let mut input = builder.new_input(&stream, Pipeline);
+        let (mut output, forwarded) = builder.new_output::<CapacityContainerBuilder<Vec<u64>>>();
+        builder.build_budgeted(move |_capabilities| {
+            let mut pending = VecDeque::new();
+            move |_frontiers, budget| {
+                input.for_each(|time, data| {
+                    let capability = time.retain();
+                    pending.extend(data.iter().map(|x| (capability.clone(), *x)));
+                });
+                let mut output = output.activate();
+                let mut count = 0;
+                while !budget.exhausted() {
+                    match pending.pop_front() {
+                        Some((capability, record)) => {
+                            output.session(&capability).give(record);
+                            budget.consume(1);
+                            count += 1;
+                        }
+                        None => break,
+                    }
+                }
+                if !pending.is_empty() {
+                    budget.yield_now();
+                }
+                invocations2.borrow_mut().push(count);
+            }
+        });
+        forwarded.inspect(move |x| received2.borrow_mut().push(*x));
+    });
+
+    while worker.has_dataflows() {
+        worker.step();
+    }
+
+    assert_eq!(*received.borrow(), (0 .. 1000).collect::<Vec<_>>());
+    let invocations = invocations.borrow();
+    assert!(invocations.iter().all(|count| *count <= 10));
+    assert!(invocations.iter().filter(|count| **count == 10).count() >= 100);
+}
+
+#[test]
+fn unbounded_by_default() {
+
+    let invocations = Rc::new(RefCell::new(Vec::new()));
+    let invocations2 = invocations.clone();
+    let mut worker = Worker::new(WorkerConfig::default(), Thread::new());
+    worker.dataflow::<u64,_,_>(move |scope| {
+        let stream = (0 .. 1000u64).to_stream(scope);
+        let mut builder = OperatorBuilder::new("Unbudgeted".to_owned(), scope.clone());
+        let mut input = builder.new_input(&stream, Pipeline);
+        builder.build_budgeted(move |_capabilities| {
+            move |_frontiers, budget| {
+                assert!(!budget.exhausted());
+                assert_eq!(budget.fuel(), None);
+                assert_eq!(budget.time_remaining(), None);
+                let mut count = 0;
+                input.for_each(|_time, data| count += data.len());
+                invocations2.borrow_mut().push(count);
+            }
+        });
+    });
+
+    while worker.has_dataflows() {
+        worker.step();
+    }
+
+    assert_eq!(invocations.borrow().iter().sum::<usize>(), 1000);
+}
@@ -0,0 +1,93 @@
+use timely::{Config, WorkerConfig};
+use timely::communication::allocator::Thread;
+use timely::dataflow::operators::{Concat, ConnectLoop, Exchange, Feedback, Filter, Map, Probe, ToStream};
+use timely::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use timely::progress::frontier::MutableAntichain;
+use timely::scheduling::Scheduler;
+use timely::worker::Worker;
+
+fn checked_worker() -> Worker<Thread> {
+    Worker::new(WorkerConfig::default().validate_progress(true), Thread::new())
+}
+
+#[test]
+fn well_formed_dataflows() {
+    let config = Config {
+        communication: timely::CommunicationConfig::Process(3),
+        worker: WorkerConfig::default().validate_progress(true),
+    };
+    timely::execute(config, |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback::<Vec<u64>>(1);
+            (0 .. 10u64)
+                .to_stream(scope)
+                .concat(&cycle)
+                .exchange(|x| *x)
+                .map(|x| x + 1)
+                .filter(|x| *x < 20)
+                .connect_loop(handle);
+        });
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+}
+
+#[test]
+#[should_panic(expected = "Progress violation at operator \"Negative\"")]
+fn negative_capability() {
+    let mut worker = checked_worker();
+    worker.dataflow::<u64,_,_>(|scope| {
+        let mut builder = OperatorBuilder::new("Negative".to_owned(), scope.clone());
+        let (_output, stream) = builder.new_output::<Vec<()>>();
+        builder.build(|progress| {
+            progress.internals[0].update(0, -2);
+            false
+        });
+        stream.probe();
+    });
+    while worker.has_dataflows() { worker.step(); }
+}
+
+#[test]
+#[should_panic(expected = "without a capability")]
+fn produced_without_capability() {
+    let mut worker = checked_worker();
+    worker.dataflow::<u64,_,_>(|scope| {
+        let mut builder = OperatorBuilder::new("Unsupported".to_owned(), scope.clone());
+        let (_output, stream) = builder.new_output::<Vec<()>>();
+        let activator = scope.activator_for(&builder.operator_info().address[..]);
+        let mut round = 0;
+        builder.build(move |progress| {
+            round += 1;
+            match round {
+                1 => progress.internals[0].update(0, -1),
+                3 => progress.produceds[0].update(5, 1),
+                _ => { },
+            }
+            activator.activate();
+            false
+        });
+        stream.probe();
+    });
+    while worker.has_dataflows() { worker.step(); }
+}
+
+#[test]
+#[should_panic(expected = "more messages consumed than produced")]
+fn consumed_without_production() {
+    let mut worker = checked_worker();
+    worker.dataflow::<u64,_,_>(|scope| {
+        let input = (0 .. 0u64).to_stream(scope);
+        let mut builder = OperatorBuilder::new("Overconsume".to_owned(), scope.clone());
+        let _puller = builder.new_input(&input, timely::dataflow::channels::pact::Pipeline);
+        let mut frontier = MutableAntichain::new();
+        let mut done = false;
+        builder.build(move |progress| {
+            frontier.update_iter(progress.frontiers[0].drain());
+            if frontier.is_empty() && !done {
+                progress.consumeds[0].update(0, 1);
+                done = true;
+            }
+            false
+        });
+    });
+    while worker.has_dataflows() { worker.step(); }
+}
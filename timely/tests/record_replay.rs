@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use timely::WorkerConfig;
+use timely::communication::Allocate;
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Exchange, Input, Inspect, Map, Probe};
+use timely::record::Replay;
+use timely::worker::Worker;
+
+/// Builds a dataflow that exchanges records, and reports each record a worker receives.
+fn build<A: Allocate, F: Fn(u64, u64)+'static>(worker: &mut Worker<A>, observe: F) -> (InputHandle<u64, u64>, ProbeHandle<u64>) {
+    let mut input = InputHandle::new();
+    let probe = worker.dataflow(|scope| {
+        scope.input_from(&mut input)
+             .exchange(|x| *x / 3)
+             .map(|x| x * 2)
+             .inspect_time(move |time, x| observe(*time, *x))
+             .probe()
+    });
+    (input, probe)
+}
+
+#[test]
+fn replay_reproduces_worker() {
+
+    let path = std::env::temp_dir().join(format!("timely-record-replay-{}", std::process::id()));
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+
+    let path2 = path.clone();
+    let recorded2 = recorded.clone();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let recorder = if worker.index() == 1 {
+            Some(worker.record(std::io::BufWriter::new(std::fs::File::create(&path2).unwrap())))
+        } else { None };
+
+        let index = worker.index();
+        let recorded = recorded2.clone();
+        let (mut input, probe) = build(worker, move |time, x| {
+            if index == 1 { recorded.lock().unwrap().push((time, x)); }
+        });
+        if let Some(recorder) = &recorder {
+            recorder.record_input(&mut input);
+        }
+
+        // Each worker introduces different records, at a different pace.
+        for round in 0 .. 20 {
+            for x in 0 .. (round + worker.index() as u64) {
+                input.send(100 * round + x);
+            }
+            input.advance_to(round + 1);
+            if round % (worker.index() as u64 + 1) == 0 {
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+
+    let recorded = recorded.lock().unwrap().clone();
+    assert!(!recorded.is_empty());
+
+    let mut replay = Replay::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut worker = Worker::new(WorkerConfig::default(), replay.allocator());
+    assert_eq!(worker.index(), 1);
+    assert_eq!(worker.peers(), 3);
+
+    let replayed = Rc::new(RefCell::new(Vec::new()));
+    let replayed2 = replayed.clone();
+    let (input, probe) = build(&mut worker, move |time, x| replayed2.borrow_mut().push((time, x)));
+    replay.replay_input(input);
+    replay.run(&mut worker);
+
+    assert_eq!(*replayed.borrow(), recorded);
+    assert!(!probe.less_than(&20));
+    assert!(!worker.has_dataflows());
+
+    std::fs::remove_file(&path).ok();
+}
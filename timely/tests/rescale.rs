@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::rescale::{execute_rescalable, route, RescaleHandle};
+
+#[test]
+fn rescale_up_and_down() {
+
+    // Generation `i` runs with `schedule[i].0` workers, and ends at epoch `schedule[i].1`.
+    let schedule = vec![(2, 3), (4, 6), (1, 9)];
+    let handle = RescaleHandle::new();
+
+    let initial = (0 .. 20u64).map(|key| (key, 0u64)).collect();
+    let handle2 = handle.clone();
+    let state = execute_rescalable(schedule[0].0, Default::default(), handle.clone(), initial, move |worker, generation| {
+
+        let index = generation.index();
+        assert_eq!(worker.peers(), schedule[index].0);
+        assert_eq!(generation.start(), if index == 0 { 0 } else { schedule[index - 1].1 });
+
+        // Each worker requests the same rescaling; only the first request is recorded.
+        if let Some(&(workers, _)) = schedule.get(index + 1) {
+            handle2.rescale(workers, schedule[index].1);
+        }
+
+        // Each worker holds the state for the keys routed to it.
+        let peers = worker.peers() as u64;
+        let me = worker.index() as u64;
+        let state = Rc::new(RefCell::new(generation.take_state().into_iter().collect::<HashMap<_,_>>()));
+        assert!(state.borrow().keys().all(|key| route(key) % peers == me));
+
+        let mut input = InputHandle::new();
+        let state2 = state.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|key: &u64| route(key))
+                 .inspect(move |key| *state2.borrow_mut().get_mut(key).expect("key routed to wrong worker") += 1)
+                 .probe()
+        });
+
+        let mut epoch = generation.start();
+        input.advance_to(epoch);
+        while epoch < schedule[index].1 && generation.try_begin(epoch) {
+            if worker.index() == 0 {
+                for key in 0 .. 20u64 {
+                    input.send(key);
+                }
+            }
+            epoch += 1;
+            input.advance_to(epoch);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+        assert_eq!(epoch, schedule[index].1);
+
+        let result = state.borrow_mut().drain().collect();
+        result
+    }).unwrap();
+
+    assert_eq!(handle.pending(), None);
+    let mut state = state;
+    state.sort();
+    assert_eq!(state, (0 .. 20u64).map(|key| (key, 9)).collect::<Vec<_>>());
+}
+
+#[test]
+fn rescale_rejects_past_epochs() {
+    let handle = RescaleHandle::new();
+    let handle2 = handle.clone();
+    let initial = (0 .. 4u64).map(|key| (key, 0u64)).collect();
+    let state = execute_rescalable(1, Default::default(), handle.clone(), initial, move |_worker, generation| {
+        if generation.index() == 0 {
+            assert!(generation.try_begin(0));
+            assert!(generation.try_begin(1));
+            // Epochs that have begun, or that precede them, cannot be boundaries.
+            assert!(!handle2.rescale(2, 0));
+            assert!(!handle2.rescale(2, 1));
+            assert!(handle2.rescale(2, 2));
+            assert!(!generation.try_begin(2));
+            assert_eq!(generation.boundary(), Some(2));
+        }
+        else {
+            assert_eq!(generation.start(), 2);
+            // The start of the generation cannot be a boundary, even before it has begun.
+            assert!(!handle2.rescale(1, 2));
+            assert!(generation.try_begin(2));
+        }
+        generation.take_state()
+    }).unwrap();
+    assert_eq!(state.len(), 4);
+}
@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+use timely::dataflow::operators::capture::{Event, EventPusher, RotatingReader, RotatingWriter};
+use timely::dataflow::operators::capture::event::EventIterator;
+use timely::dataflow::operators::capture::event::rotating::{segments, SegmentHeader};
+use timely::logging::{BatchLogger, TimelyEvent};
+
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("timely-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+#[test]
+fn rotating_log_segments() {
+    let directory = directory("rotating-log");
+    let directory2 = directory.clone();
+
+    timely::execute_directly(move |worker| {
+        let writer = RotatingWriter::new(&directory2, "timely", worker.index()).max_bytes(1 << 12);
+        let mut logger = BatchLogger::new(writer);
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |time, data| {
+            logger.publish_batch(time, data);
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .inspect(|_| { })
+                 .probe()
+        });
+        for round in 0 .. 100u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+        worker.log_register().remove("timely");
+    });
+
+    let segments = segments(&directory, "timely", 0).unwrap();
+    assert!(segments.len() > 1);
+    for (index, (segment, path)) in segments.iter().enumerate() {
+        assert_eq!(*segment, index as u64);
+        let header = SegmentHeader::read_from(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(header, SegmentHeader::new::<Duration, Vec<(Duration, usize, TimelyEvent)>>(0, *segment));
+        assert_eq!(header.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    // Segments are read in order, and their events in the order they were written.
+    let mut reader = RotatingReader::<Duration, Vec<(Duration, usize, TimelyEvent)>>::open(&directory, "timely", 0).unwrap();
+    let mut last = Duration::default();
+    let mut messages = 0;
+    let mut closed = false;
+    while let Some(event) = reader.next() {
+        match event {
+            Event::Messages(time, data) => {
+                assert!(*time >= last);
+                last = *time;
+                messages += data.iter().filter(|(_, _, event)| matches!(event, TimelyEvent::Messages(_))).count();
+                closed = false;
+            },
+            Event::Progress(updates) => {
+                closed = updates.iter().all(|(_, diff)| *diff < 0);
+            },
+        }
+    }
+    assert_eq!(reader.header().unwrap().segment, segments.last().unwrap().0);
+    assert!(messages >= 200);
+    // The logger closes the stream when it is removed.
+    assert!(closed);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn rotating_log_bounded() {
+    let directory = directory("rotating-bounded");
+
+    let mut writer = RotatingWriter::<u64, Vec<u64>>::new(&directory, "numbers", 3)
+        .max_bytes(1 << 10)
+        .max_segments(3);
+    for round in 0 .. 100u64 {
+        writer.push(Event::Messages(round, vec![round; 16]));
+    }
+    drop(writer);
+
+    let retained = segments(&directory, "numbers", 3).unwrap();
+    assert_eq!(retained.len(), 3);
+    let total: u64 = retained.iter().map(|(_, path)| std::fs::metadata(path).unwrap().len()).sum();
+    assert!(total <= 3 * ((1 << 10) + 256));
+
+    // Reading continues from the oldest remaining segment, up to the last event written.
+    let mut reader = RotatingReader::<u64, Vec<u64>>::open(&directory, "numbers", 3).unwrap();
+    assert_eq!(reader.header().unwrap().worker, 3);
+    let mut rounds = Vec::new();
+    while let Some(event) = reader.next() {
+        if let Event::Messages(round, data) = event {
+            assert_eq!(data, &vec![*round; 16]);
+            rounds.push(*round);
+        }
+    }
+    assert!(rounds.len() < 100);
+    assert_eq!(rounds.last(), Some(&99));
+    assert!(rounds.windows(2).all(|pair| pair[1] == pair[0] + 1));
+
+    // Writers continue after existing segments.
+    let mut writer = RotatingWriter::<u64, Vec<u64>>::new(&directory, "numbers", 3);
+    writer.push(Event::Progress(vec![(100, -1)]));
+    drop(writer);
+    let last = segments(&directory, "numbers", 3).unwrap().last().unwrap().0;
+    assert_eq!(last, retained.last().unwrap().0 + 1);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn rotating_log_workers() {
+    let directory = directory("rotating-workers");
+
+    // Workers sharing a directory and prefix write and read their own segments.
+    let mut writers = (0 .. 2).map(|worker| {
+        RotatingWriter::<u64, Vec<usize>>::new(&directory, "workers", worker).max_bytes(1 << 8)
+    }).collect::<Vec<_>>();
+    for round in 0 .. 20u64 {
+        for (worker, writer) in writers.iter_mut().enumerate() {
+            writer.push(Event::Messages(round, vec![worker; 16]));
+        }
+    }
+    drop(writers);
+
+    for worker in 0 .. 2 {
+        let retained = segments(&directory, "workers", worker).unwrap();
+        assert!(retained.len() > 1);
+        assert!(retained.iter().enumerate().all(|(index, (segment, _))| *segment == index as u64));
+
+        let mut reader = RotatingReader::<u64, Vec<usize>>::open(&directory, "workers", worker).unwrap();
+        let mut rounds = 0;
+        while let Some(event) = reader.next() {
+            if let Event::Messages(_, data) = event {
+                assert!(data.iter().all(|x| *x == worker));
+                rounds += 1;
+            }
+        }
+        assert_eq!(rounds, 20);
+        assert_eq!(reader.header().unwrap().worker, worker);
+    }
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::WorkerConfig;
+use timely::communication::allocator::Thread;
+use timely::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use timely::scheduling::Scheduler;
+use timely::worker::Worker;
+
+/// Installs a dataflow whose one operator records `name` each time it is scheduled,
+/// and keeps itself active until it has been scheduled `rounds` times.
+fn recording_dataflow(worker: &mut Worker<Thread>, name: &'static str, priority: i64, rounds: usize, log: Rc<RefCell<Vec<&'static str>>>) {
+    worker.dataflow_core_prioritized::<u64,_,_,_>(name, None, (), priority, move |_, scope| {
+        let mut builder = OperatorBuilder::new(name.to_owned(), scope.clone());
+        let (_output, _stream) = builder.new_output::<Vec<()>>();
+        let activator = scope.activator_for(&builder.operator_info().address[..]);
+        let mut scheduled = 0;
+        builder.build(move |progress| {
+            if scheduled < rounds {
+                log.borrow_mut().push(name);
+                scheduled += 1;
+                if scheduled < rounds {
+                    activator.activate();
+                }
+                else {
+                    progress.internals[0].update(0, -1);
+                }
+            }
+            scheduled < rounds
+        });
+    });
+}
+
+#[test]
+fn priority_order() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut worker = Worker::new(WorkerConfig::default(), Thread::new());
+    recording_dataflow(&mut worker, "batch", 0, 3, log.clone());
+    recording_dataflow(&mut worker, "query", 10, 3, log.clone());
+    worker.step();
+    assert_eq!(*log.borrow(), vec!["query", "batch"]);
+
+    // Re-prioritizing the batch dataflow changes the order.
+    assert!(worker.set_dataflow_priority(0, 20));
+    worker.step();
+    assert_eq!(&log.borrow()[2..], &["batch", "query"]);
+}
+
+#[test]
+fn step_budget_defers_without_starvation() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let config = WorkerConfig::default().step_budget(Some(Duration::from_secs(0)));
+    let mut worker = Worker::new(config, Thread::new());
+    recording_dataflow(&mut worker, "batch", 0, 2, log.clone());
+    recording_dataflow(&mut worker, "query", 10, 2, log.clone());
+    while worker.has_dataflows() {
+        worker.step();
+    }
+    // One dataflow per step, with the deferred dataflow scheduled first in the next step.
+    assert_eq!(*log.borrow(), vec!["query", "batch", "query", "batch"]);
+}
+
+#[test]
+fn step_budget_defers_once() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let config = WorkerConfig::default().step_budget(Some(Duration::from_secs(0)));
+    let mut worker = Worker::new(config, Thread::new());
+    recording_dataflow(&mut worker, "a", 30, 2, log.clone());
+    recording_dataflow(&mut worker, "b", 20, 2, log.clone());
+    recording_dataflow(&mut worker, "c", 10, 2, log.clone());
+    worker.step();
+    assert_eq!(*log.borrow(), vec!["a"]);
+    // Both deferred dataflows are scheduled, although the budget is exhausted by the first.
+    worker.step();
+    assert_eq!(*log.borrow(), vec!["a", "b", "c"]);
+    while worker.has_dataflows() {
+        worker.step();
+    }
+    assert_eq!(*log.borrow(), vec!["a", "b", "c", "a", "b", "c"]);
+}
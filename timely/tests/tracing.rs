@@ -0,0 +1,63 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::{Event, Id, Metadata, Subscriber};
+use tracing::span::{Attributes, Record};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+
+/// A subscriber that records the names of spans as they are entered.
+#[derive(Clone, Default)]
+struct Spans {
+    /// Span names, by span identifier less one.
+    names: Arc<Mutex<Vec<&'static str>>>,
+    entered: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool { true }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut names = self.names.lock().unwrap();
+        names.push(span.metadata().name());
+        Id::from_u64(names.len() as u64)
+    }
+    fn record(&self, _span: &Id, _values: &Record<'_>) { }
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) { }
+    fn event(&self, _event: &Event<'_>) { }
+    fn enter(&self, span: &Id) {
+        let name = self.names.lock().unwrap()[span.into_u64() as usize - 1];
+        self.entered.lock().unwrap().push(name);
+    }
+    fn exit(&self, _span: &Id) { }
+}
+
+#[test]
+fn tracing_schedule_and_park_spans() {
+    let spans = Spans::default();
+    tracing::subscriber::with_default(spans.clone(), || {
+        timely::execute_directly(|worker| {
+            let mut input = InputHandle::<u64, u64>::new();
+            let probe = worker.dataflow(|scope| {
+                scope.input_from(&mut input)
+                     .inspect(|_| { })
+                     .probe()
+            });
+            input.send(0);
+            input.advance_to(1);
+            worker.step_while(|| probe.less_than(&1));
+
+            // With nothing left to do, the worker parks.
+            for _ in 0 .. 10 {
+                worker.step_or_park(Some(Duration::from_millis(1)));
+            }
+        });
+    });
+
+    let entered = spans.entered.lock().unwrap();
+    assert!(entered.contains(&"schedule"));
+    assert!(entered.contains(&"park"));
+    assert!(entered.iter().all(|name| *name == "schedule" || *name == "park"));
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe, ToStream};
+
+#[test]
+fn utilization_operators_and_dataflows() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .inspect(|_| { })
+                 .probe()
+        });
+
+        for round in 0 .. 10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+
+        let utilization = worker.utilization();
+        let dataflow = &utilization.dataflows[&0];
+        assert!(dataflow.steps > 0);
+        assert!(utilization.steps >= dataflow.steps);
+        assert!(utilization.busy() <= utilization.elapsed);
+
+        let inspect = utilization.operators.values().find(|operator| operator.name == "InspectBatch").unwrap();
+        assert!(inspect.activations >= 10);
+        assert!(utilization.activations >= inspect.activations);
+
+        // Counters of completed dataflows are discarded, but the worker's totals remain.
+        drop(input);
+        while worker.step() { }
+        let completed = worker.utilization();
+        assert!(completed.dataflows.is_empty());
+        assert!(completed.operators.is_empty());
+        assert!(completed.activations >= utilization.activations);
+        assert!(completed.steps > utilization.steps);
+    });
+}
+
+#[test]
+fn utilization_parking() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        worker.dataflow(|scope| { scope.input_from(&mut input); });
+        while worker.step() { if worker.utilization().steps > 10 { break; } }
+
+        let before = worker.utilization();
+        worker.step_or_park(Some(Duration::from_millis(10)));
+        let after = worker.utilization();
+        assert_eq!(after.parks, before.parks + 1);
+        assert_eq!(after.idle_steps, before.idle_steps + 1);
+        assert!(after.parked > before.parked);
+    });
+}
+
+#[test]
+fn utilization_from_operator() {
+    timely::execute_directly(|worker| {
+        let steps = Rc::new(RefCell::new(Vec::new()));
+        let steps2 = steps.clone();
+        let worker2 = worker.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64)
+                .to_stream(scope)
+                .inspect(move |_| steps2.borrow_mut().push(worker2.utilization().steps));
+        });
+        while worker.step() { }
+
+        // Operators may read the counters while the worker steps their dataflow.
+        assert_eq!(steps.borrow().len(), 10);
+        let utilization = worker.utilization();
+        assert!(utilization.dataflows.is_empty());
+        assert!(utilization.steps > 0);
+    });
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use timely::dataflow::operators::{Exchange, Inspect};
+use timely::dataflow::operators::generic::operator::source;
+use timely::scheduling::Scheduler;
+use timely::worker::AsWorker;
+
+#[test]
+fn panic_aborts_other_workers() {
+    let guards = timely::execute(timely::Config::process(3), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let peers = scope.peers() as u64;
+            // A source that holds its capability forever, so the dataflow never completes.
+            source(scope, "Endless", |capability, info| {
+                let activator = scope.activator_for(&info.address[..]);
+                let mut round = 0;
+                move |output| {
+                    output.session(&capability).give(round % peers);
+                    round += 1;
+                    activator.activate_after(Duration::from_millis(1));
+                }
+            })
+            .container::<Vec<_>>()
+            .exchange(|x| *x)
+            .inspect(|_x| { });
+        });
+        if worker.index() == 1 {
+            for _ in 0 .. 10 {
+                worker.step();
+            }
+            panic!("boom");
+        }
+    }).unwrap();
+
+    let cancel = guards.cancel_handle();
+    let results = guards.join();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1], Err("Worker panic: boom".to_string()));
+    // The other workers stop, and keep their results.
+    for index in [0, 2] {
+        assert_eq!(results[index], Ok(()));
+    }
+    assert_eq!(cancel.poisoned(), Some((1, "boom".to_string())));
+}
+
+#[test]
+#[should_panic(expected = "Worker panic: worker 0 panicked first: boom")]
+fn drop_reports_first_panic() {
+    let _guards = timely::execute(timely::Config::process(2), |worker| {
+        if worker.index() == 0 {
+            panic!("boom");
+        }
+    }).unwrap();
+}